@@ -0,0 +1,593 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Shl, Shr, Sub},
+};
+
+/*Integers too big for an i64. Ints turn into these when they overflow and back again once they fit
+so a BigInt never holds something an i64 could. The size is kept in base 2^32, lowest digit first */
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>, // No zeros on the end. Zero has no digits at all
+}
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u32>) -> BigInt {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        BigInt {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    // How many bits the size takes up
+    pub fn bits(&self) -> u64 {
+        match self.digits.last() {
+            Some(top) => (self.digits.len() as u64 - 1) * 32 + (32 - top.leading_zeros()) as u64,
+            None => 0,
+        }
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::new(false, self.digits.clone())
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let size = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0u64, |acc, d| (acc << 32) | *d as u64) as i128;
+        i64::try_from(if self.negative { -size } else { size }).ok()
+    }
+
+    pub fn to_u32(&self) -> Option<u32> {
+        match self.digits.as_slice() {
+            [] => Some(0),
+            [d] if !self.negative => Some(*d),
+            _ => None,
+        }
+    }
+
+    // Rounds when there are more digits than a float can hold
+    pub fn to_f64(&self) -> f64 {
+        let size = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |acc, d| acc * 4294967296.0 + *d as f64);
+        if self.negative {
+            -size
+        } else {
+            size
+        }
+    }
+
+    // Only whole floats have a BigInt. Every one of them does, however big
+    pub fn from_f64(n: f64) -> Option<BigInt> {
+        if !n.is_finite() || n.fract() != 0.0 {
+            return None;
+        }
+        let bits = n.abs().to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        if exponent == 0 {
+            return Some(BigInt::default());
+        }
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let shift = exponent - 1075;
+        let size = match shift >= 0 {
+            true => &BigInt::from(mantissa as i64) << shift as usize,
+            false => BigInt::from((mantissa >> -shift) as i64),
+        };
+        Some(if n < 0.0 { -&size } else { size })
+    }
+
+    // Digits in the given radix with an optional sign in front. 12345678901234567890 // -ff
+    pub fn parse(text: &str, radix: u32) -> Option<BigInt> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        if text.is_empty() {
+            return None;
+        }
+        let mut digits = vec![];
+        for c in text.chars() {
+            mul_small(&mut digits, radix, c.to_digit(radix)?);
+        }
+        Some(BigInt::new(negative, digits))
+    }
+
+    // The digits in two's complement, len digits long
+    fn twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut digits = self.digits.clone();
+        digits.resize(len, 0);
+        if self.negative {
+            negate_digits(&mut digits);
+        }
+        digits
+    }
+
+    /*& | ^ act as if both numbers were in two's complement with as many digits as they need.
+    The extra digit on top holds the sign */
+    fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        let len = self.digits.len().max(other.digits.len()) + 1;
+        let (a, b) = (self.twos_complement(len), other.twos_complement(len));
+        let mut digits: Vec<u32> = a.iter().zip(&b).map(|(a, b)| op(*a, *b)).collect();
+        let negative = digits.last().is_some_and(|d| d >> 31 == 1);
+        if negative {
+            negate_digits(&mut digits);
+        }
+        BigInt::new(negative, digits)
+    }
+
+    // Rounds the quotient towards negative infinity so the remainder has the sign of the divisor. None for / 0
+    pub fn div_mod_floor(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (q, r) = div_rem(&self.digits, &other.digits);
+        let q = BigInt::new(self.negative != other.negative, q);
+        let r = BigInt::new(self.negative, r);
+        if !r.is_zero() && r.negative != other.negative {
+            return Some((&q - &BigInt::from(1), &r + other));
+        }
+        Some((q, r))
+    }
+
+    /*The float nearest self / other. Turning both into floats first gives inf / inf once they are past
+    the float range, so the quotient is worked out to about 64 bits and scaled back. None for / 0 */
+    pub fn div_f64(&self, other: &BigInt) -> Option<f64> {
+        if other.is_zero() {
+            return None;
+        }
+        let shift = 64 + other.bits() as i64 - self.bits() as i64;
+        let (a, b) = match shift >= 0 {
+            true => (&self.abs() << shift as usize, other.abs()),
+            false => (self.abs(), &other.abs() << -shift as usize),
+        };
+        let (q, _) = div_rem(&a.digits, &b.digits);
+        let size = scale(BigInt::new(false, q).to_f64(), -shift);
+        Some(match self.negative != other.negative {
+            true => -size,
+            false => size,
+        })
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> BigInt {
+        let size = n.unsigned_abs();
+        BigInt::new(n < 0, vec![size as u32, (size >> 32) as u32])
+    }
+}
+
+// x * 2^exp without 2^exp turning into inf or 0 on its own first
+fn scale(mut x: f64, mut exp: i64) -> f64 {
+    while exp.abs() > 1000 {
+        let step = exp.signum() * 1000;
+        x *= 2f64.powi(step as i32);
+        exp -= step;
+    }
+    x * 2f64.powi(exp as i32)
+}
+
+// Flips every bit and adds one, going between a negative number and its two's complement
+fn negate_digits(digits: &mut [u32]) {
+    let mut carry = true;
+    for d in digits.iter_mut() {
+        let (sum, overflow) = (!*d).overflowing_add(carry as u32);
+        *d = sum;
+        carry = overflow;
+    }
+}
+
+fn compare_digits(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_digits(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        digits.push(sum as u32);
+        carry = sum >> 32;
+    }
+    digits.push(carry as u32);
+    digits
+}
+
+// a has to be at least as big as b
+fn sub_digits(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, d) in a.iter().enumerate() {
+        let mut diff = *d as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = (diff < 0) as i64;
+        if diff < 0 {
+            diff += 1 << 32;
+        }
+        digits.push(diff as u32);
+    }
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+// digits = digits * m + add
+fn mul_small(digits: &mut Vec<u32>, m: u32, add: u32) {
+    let mut carry = add as u64;
+    for d in digits.iter_mut() {
+        let product = *d as u64 * m as u64 + carry;
+        *d = product as u32;
+        carry = product >> 32;
+    }
+    if carry > 0 {
+        digits.push(carry as u32);
+    }
+}
+
+fn div_small(digits: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0; digits.len()];
+    let mut rem = 0u64;
+    for (i, d) in digits.iter().enumerate().rev() {
+        let current = (rem << 32) | *d as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        rem = current % divisor as u64;
+    }
+    (quotient, rem as u32)
+}
+
+/*Long division one bit at a time. Slow for huge numbers but the numbers scripts make are small enough.
+Dividing by a single digit is common (printing divides by 10^9 over and over) so that gets its own path */
+fn div_rem(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_digits(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    if let [divisor] = b {
+        let (q, r) = div_small(a, *divisor);
+        return (q, vec![r]);
+    }
+    let mut quotient = vec![0; a.len()];
+    let mut rem: Vec<u32> = vec![];
+    for i in (0..a.len() * 32).rev() {
+        mul_small(&mut rem, 2, (a[i / 32] >> (i % 32)) & 1);
+        if compare_digits(&rem, b) != Ordering::Less {
+            rem = sub_digits(&rem, b);
+            quotient[i / 32] |= 1 << (i % 32);
+        }
+    }
+    (quotient, rem)
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_digits(&self.digits, &other.digits));
+        }
+        match compare_digits(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::new(other.negative, sub_digits(&other.digits, &self.digits)),
+            _ => BigInt::new(self.negative, sub_digits(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.digits.iter().enumerate() {
+                let product = *a as u64 * *b as u64 + digits[i + j] as u64 + carry;
+                digits[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        BigInt::new(self.negative != other.negative, digits)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+}
+
+impl Shl<usize> for &BigInt {
+    type Output = BigInt;
+
+    fn shl(self, bits: usize) -> BigInt {
+        let mut digits = vec![0; bits / 32];
+        let shift = bits % 32;
+        let mut carry = 0;
+        for d in &self.digits {
+            let wide = (*d as u64) << shift;
+            digits.push(wide as u32 | carry);
+            carry = (wide >> 32) as u32;
+        }
+        digits.push(carry);
+        BigInt::new(self.negative, digits)
+    }
+}
+
+// Rounds towards negative infinity like >> on an i64 does, so -5 >> 1 is -3
+impl Shr<usize> for &BigInt {
+    type Output = BigInt;
+
+    fn shr(self, bits: usize) -> BigInt {
+        let (words, shift) = (bits / 32, bits % 32);
+        if words >= self.digits.len() {
+            return BigInt::from(if self.negative { -1 } else { 0 });
+        }
+        let mut digits = Vec::with_capacity(self.digits.len() - words);
+        for i in words..self.digits.len() {
+            let high = self.digits.get(i + 1).map_or(0, |d| (*d as u64) << 32);
+            digits.push(((high | self.digits[i] as u64) >> shift) as u32);
+        }
+        let result = BigInt::new(self.negative, digits);
+        let lost = self.digits[..words].iter().any(|d| *d != 0)
+            || self.digits[words] & ((1 << shift) - 1) != 0;
+        match self.negative && lost {
+            true => &result - &BigInt::from(1),
+            false => result,
+        }
+    }
+}
+
+impl BitAnd for &BigInt {
+    type Output = BigInt;
+
+    fn bitand(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BigInt {
+    type Output = BigInt;
+
+    fn bitor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BigInt {
+    type Output = BigInt;
+
+    fn bitxor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_digits(&self.digits, &other.digits),
+            (true, true) => compare_digits(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Nine decimal digits at a time, the first chunk is the only one without leading zeros
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+        let mut chunks = vec![];
+        let mut digits = self.digits.clone();
+        while !digits.is_empty() {
+            let (q, r) = div_small(&digits, 1_000_000_000);
+            chunks.push(r);
+            digits = BigInt::new(false, q).digits;
+        }
+        if self.negative {
+            f.write_str("-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BigInt({})", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{int_value, Value};
+
+    fn big(text: &str) -> BigInt {
+        BigInt::parse(text, 10).unwrap()
+    }
+
+    // Numbers either side of the 32 bit digit and the i64 limits, with both signs
+    fn samples() -> Vec<i128> {
+        let mut samples = vec![
+            0,
+            1,
+            2,
+            3,
+            7,
+            1 << 31,
+            1 << 32,
+            (1 << 32) + 1,
+            1 << 63,
+            1 << 64,
+        ];
+        samples.extend([i64::MAX as i128, i64::MAX as i128 + 1, u64::MAX as i128]);
+        let negatives: Vec<i128> = samples.iter().map(|n| -n).collect();
+        samples.extend(negatives);
+        samples
+    }
+
+    fn from_i128(n: i128) -> BigInt {
+        big(&n.to_string())
+    }
+
+    #[test]
+    fn zero_is_never_negative() {
+        assert!(!big("-0").is_negative());
+        assert!(!(-&BigInt::from(0)).is_negative());
+        assert!(!(&BigInt::from(-5) + &BigInt::from(5)).is_negative());
+        assert!(!(&BigInt::from(-5) * &BigInt::from(0)).is_negative());
+        assert_eq!(big("-0"), BigInt::default());
+    }
+
+    #[test]
+    fn signs_of_arithmetic() {
+        for a in samples() {
+            for b in samples() {
+                let (x, y) = (from_i128(a), from_i128(b));
+                assert_eq!(&x + &y, from_i128(a + b), "{} + {}", a, b);
+                assert_eq!(&x - &y, from_i128(a - b), "{} - {}", a, b);
+                assert_eq!(x.cmp(&y), a.cmp(&b), "{} cmp {}", a, b);
+                if let Some(product) = a.checked_mul(b) {
+                    assert_eq!(&x * &y, from_i128(product), "{} * {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn floor_division_rounds_down() {
+        for a in samples() {
+            for b in samples().into_iter().filter(|b| *b != 0) {
+                let q = a.div_euclid(b) - if b < 0 && a.rem_euclid(b) != 0 { 1 } else { 0 };
+                let (bq, br) = from_i128(a).div_mod_floor(&from_i128(b)).unwrap();
+                assert_eq!(bq, from_i128(q), "{} // {}", a, b);
+                assert_eq!(br, from_i128(a - q * b), "{} % {}", a, b);
+                if br != BigInt::default() {
+                    assert_eq!(
+                        br.is_negative(),
+                        b < 0,
+                        "{} % {} has the sign of the divisor",
+                        a,
+                        b
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            big("-7").div_mod_floor(&big("2")),
+            Some((big("-4"), big("1")))
+        );
+        assert_eq!(
+            big("7").div_mod_floor(&big("-2")),
+            Some((big("-4"), big("-1")))
+        );
+        assert_eq!(
+            big("-7").div_mod_floor(&big("-2")),
+            Some((big("3"), big("-1")))
+        );
+        assert_eq!(big("5").div_mod_floor(&BigInt::default()), None);
+    }
+
+    #[test]
+    fn i64_limits() {
+        assert_eq!(BigInt::from(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(BigInt::from(i64::MIN), big("-9223372036854775808"));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+        assert_eq!(big("18446744073709551616").to_i64(), None);
+    }
+
+    #[test]
+    fn ints_turn_big_past_i64_and_back() {
+        let max = BigInt::from(i64::MAX);
+        let one = BigInt::from(1);
+        let past = &max + &one;
+        assert!(matches!(int_value(past.clone()), Value::BigInt(n) if *n == past));
+        assert!(matches!(int_value(&past - &one), Value::Int(i64::MAX)));
+        let below = &BigInt::from(i64::MIN) - &one;
+        assert!(matches!(int_value(below.clone()), Value::BigInt(_)));
+        assert!(matches!(int_value(&below + &one), Value::Int(i64::MIN)));
+        assert!(matches!(int_value(&past - &past), Value::Int(0)));
+    }
+
+    #[test]
+    fn division_into_floats() {
+        let ten = BigInt::from(10);
+        assert_eq!(ten.pow(400).div_f64(&ten.pow(399)), Some(10.0));
+        assert_eq!((-&ten.pow(400)).div_f64(&ten.pow(398)), Some(-100.0));
+        assert_eq!(BigInt::from(1).div_f64(&ten.pow(400)), Some(0.0));
+        assert_eq!(ten.pow(400).div_f64(&BigInt::from(3)), Some(f64::INFINITY));
+        assert_eq!(BigInt::from(7).div_f64(&BigInt::from(-2)), Some(-3.5));
+        assert_eq!(ten.div_f64(&BigInt::default()), None);
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        for text in ["0", "-1", "4294967296", "-123456789012345678901234567890"] {
+            assert_eq!(big(text).to_string(), text);
+        }
+        assert_eq!(BigInt::parse("-ff", 16), Some(BigInt::from(-255)));
+        assert_eq!(BigInt::parse("-", 10), None);
+        assert_eq!(BigInt::parse("12a", 10), None);
+    }
+}
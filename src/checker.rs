@@ -0,0 +1,1020 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    expression::{Expression, Param, Symbol},
+    statement::Statement,
+    token::{Literal, Token, TokenType},
+    types::Type,
+};
+
+/*Runs after the resolver and before the interpreter. Finds values that are certain to be the wrong type,
+"a" - 1 // add("a", 2) when add takes Numbers // returning a String from a function that returns a Number.
+Anything without an annotation is Any and is left to fail (or not) at runtime */
+pub struct Checker {
+    scopes: Vec<HashMap<String, Binding>>,
+    enums: HashSet<String>,
+    imported: HashSet<String>, // Imported names might be types, there is no way to know so they are Any
+    returns: Vec<(String, Type, usize)>, // Name, return type and line of the return type of each function being checked
+    current_struct: Vec<String>,         // For the type of `this`
+    pub errors: Vec<String>,
+}
+
+#[derive(Clone)]
+struct Binding {
+    ty: Type,
+    line: usize, // Where the annotation was, 0 for names without one
+    signature: Option<Signature>,
+    structure: Option<StructType>, // Set for the name of a struct or class
+}
+
+// What a call to a function needs. The rest type is the type of each extra argument
+#[derive(Clone)]
+struct Signature {
+    name: String,
+    params: Vec<(String, Type)>,
+    required: usize,
+    rest: Option<Type>,
+    ret: Type,
+}
+
+// The fields and methods of a struct or class. Classes only get their fields in init so they have none here
+#[derive(Clone, Default)]
+struct StructType {
+    is_class: bool,
+    fields: HashMap<String, Type>,
+    methods: HashMap<String, Signature>,
+    superclass: Option<String>,
+}
+
+impl Checker {
+    pub fn new() -> Checker {
+        let mut checker = Checker {
+            scopes: vec![HashMap::new()],
+            enums: HashSet::from(["Result".to_string()]),
+            imported: HashSet::new(),
+            returns: vec![],
+            current_struct: vec![],
+            errors: vec![],
+        };
+        use Type::*;
+        let natives = [
+            (
+                "pow",
+                vec![Number, Number],
+                None,
+                Named("Result".to_string()),
+            ),
+            ("abs", vec![Number], None, Named("Result".to_string())),
+            ("min", vec![Number], Some(Number), Number),
+            ("max", vec![Number], Some(Number), Number),
+            ("range", vec![Int, Int], None, Range),
+            ("divmod", vec![Number, Number], None, Tuple),
+            ("len", vec![Any], None, Int),
+            ("int", vec![Any], None, Int),
+            ("float", vec![Any], None, Float),
+            ("push", vec![List, Any], None, Nil),
+            ("pop", vec![List], None, Any),
+            ("keys", vec![Dict], None, List),
+            ("values", vec![Dict], None, List),
+            ("has", vec![Dict, Any], None, Bool),
+            ("remove", vec![Dict, Any], None, Any),
+            ("print", vec![], Some(Any), Nil),
+            ("println", vec![], Some(Any), Nil),
+            ("Ok", vec![Any], None, Named("Result".to_string())),
+            ("Err", vec![Any], None, Named("Result".to_string())),
+        ];
+        for (name, params, rest, ret) in natives {
+            let signature = Signature {
+                name: name.to_string(),
+                required: params.len(),
+                params: params
+                    .into_iter()
+                    .map(|t| (Default::default(), t))
+                    .collect(),
+                rest,
+                ret,
+            };
+            checker.bind_function(name, signature);
+        }
+        // error("message") // error("kind", "message")
+        let error = Signature {
+            name: "error".to_string(),
+            params: vec![(Default::default(), Any), (Default::default(), Any)],
+            required: 1,
+            rest: None,
+            ret: Error,
+        };
+        checker.bind_function("error", error);
+        checker
+    }
+
+    pub fn check(&mut self, stmts: Vec<Statement>) {
+        self.scopes.push(HashMap::new());
+        self.check_stmts(stmts);
+        self.scopes.pop();
+    }
+
+    fn error(&mut self, line: usize, msg: String) {
+        let msg = format!("[Type Error] {} @Line {}", msg, line);
+        // Signatures are read once up front and again when their function is checked
+        if !self.errors.contains(&msg) {
+            self.errors.push(msg);
+        }
+    }
+
+    fn bind(&mut self, name: &str, ty: Type, line: usize) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(
+                name.to_string(),
+                Binding {
+                    ty,
+                    line,
+                    signature: None,
+                    structure: None,
+                },
+            );
+        }
+    }
+
+    fn bind_function(&mut self, name: &str, signature: Signature) {
+        if let Some(scope) = self.scopes.last_mut() {
+            let binding = Binding {
+                ty: Type::Function,
+                line: 0,
+                signature: Some(signature),
+                structure: None,
+            };
+            scope.insert(name.to_string(), binding);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|s| s.get(name))
+    }
+
+    // Struct types are scoped like any other name so a local struct hides the one outside it
+    fn struct_type(&self, name: &str) -> Option<&StructType> {
+        self.lookup(name).and_then(|b| b.structure.as_ref())
+    }
+
+    fn struct_type_mut(&mut self, name: &str) -> Option<&mut StructType> {
+        let binding = self.scopes.iter_mut().rev().find_map(|s| s.get_mut(name));
+        binding.and_then(|b| b.structure.as_mut())
+    }
+
+    fn bind_struct(&mut self, name: &str, structure: StructType) {
+        if let Some(scope) = self.scopes.last_mut() {
+            let binding = Binding {
+                ty: Type::Any,
+                line: 0,
+                signature: None,
+                structure: Some(structure),
+            };
+            scope.insert(name.to_string(), binding);
+        }
+    }
+
+    // The type an annotation names. Unknown names are reported and treated as Any
+    fn annotation(&mut self, ty: &Option<Token>) -> Type {
+        let Some(token) = ty else {
+            return Type::Any;
+        };
+        match Type::from_annotation(token) {
+            Type::Named(n) if self.imported.contains(&n) => Type::Any,
+            Type::Named(n) if self.struct_type(&n).is_none() && !self.enums.contains(&n) => {
+                self.error(token.line, format!("Unknown type {}", n));
+                Type::Any
+            }
+            t => t,
+        }
+    }
+
+    fn accepts(&self, expected: &Type, actual: &Type) -> bool {
+        match (expected, actual) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            // A Number might be either. Ints can go where Floats are expected but not the other way round
+            (Type::Number, a) | (a, Type::Number) => a.is_number(),
+            (Type::Float, Type::Int) => true,
+            (Type::Named(e), Type::Named(a)) => self.is_a(a, e),
+            (e, a) => e == a,
+        }
+    }
+
+    // Instances of a subclass can be used where the class is expected
+    fn is_a(&self, name: &str, ancestor: &str) -> bool {
+        let mut current = Some(name.to_string());
+        while let Some(n) = current {
+            if n == ancestor {
+                return true;
+            }
+            current = self.struct_type(&n).and_then(|s| s.superclass.clone());
+        }
+        false
+    }
+
+    fn signature(&mut self, name: &str, params: &[Param], ret: &Option<Token>) -> Signature {
+        let mut signature = Signature {
+            name: name.to_string(),
+            params: vec![],
+            required: params
+                .iter()
+                .take_while(|p| p.default.is_none() && !p.rest)
+                .count(),
+            rest: None,
+            ret: self.annotation(ret),
+        };
+        for param in params {
+            let ty = self.annotation(&param.ty);
+            if param.rest {
+                signature.rest = Some(ty);
+            } else {
+                signature.params.push((param.name.name.clone(), ty));
+            }
+        }
+        signature
+    }
+
+    /*Struct, class and enum names are gathered before the statements around them are checked so annotations
+    can use types declared further down. Functions are too so calls to them can be checked from anywhere */
+    fn declare(&mut self, stmts: &[Statement]) {
+        for stmt in stmts {
+            match stmt {
+                Statement::StructDeclaration(name, _, _) => {
+                    self.bind_struct(&name.name, StructType::default());
+                }
+                Statement::ClassDeclaration(name, superclass, _) => {
+                    let class = StructType {
+                        is_class: true,
+                        superclass: superclass.as_ref().map(|s| s.name.clone()),
+                        ..Default::default()
+                    };
+                    self.bind_struct(&name.name, class);
+                }
+                Statement::EnumDeclaration(name, _) => {
+                    self.enums.insert(name.name.clone());
+                }
+                _ => (),
+            }
+        }
+        for stmt in stmts {
+            match stmt {
+                Statement::StructDeclaration(name, fields, methods) => {
+                    for (field, ty) in fields {
+                        let ty = self.annotation(ty);
+                        if let Some(s) = self.struct_type_mut(&name.name) {
+                            s.fields.insert(field.name.clone(), ty);
+                        }
+                    }
+                    self.declare_methods(&name.name, methods);
+                }
+                Statement::ClassDeclaration(name, _, methods)
+                | Statement::ImplDeclaration(name, methods) => {
+                    self.declare_methods(&name.name, methods)
+                }
+                Statement::FuncDclaration(name, params, ret, _) => {
+                    let signature = self.signature(&name.name, params, ret);
+                    self.bind_function(&name.name, signature);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn declare_methods(&mut self, struct_name: &str, methods: &[Statement]) {
+        for method in methods {
+            if let Statement::FuncDclaration(name, params, ret, _) = method {
+                let full_name = format!("{}.{}", struct_name, name.name);
+                let signature = self.signature(&full_name, params, ret);
+                if let Some(s) = self.struct_type_mut(struct_name) {
+                    s.methods.insert(name.name.clone(), signature);
+                }
+            }
+        }
+    }
+
+    // Checks each statement and gives back the type of the last one when it is an expression
+    fn check_stmts(&mut self, stmts: Vec<Statement>) -> Type {
+        self.declare(&stmts);
+        let mut last = Type::Any;
+        for stmt in stmts {
+            last = match stmt {
+                Statement::Expression(expr) => self.expr(expr),
+                stmt => {
+                    self.check_stmt(stmt);
+                    Type::Any
+                }
+            };
+        }
+        last
+    }
+
+    fn check_stmt(&mut self, stmt: Statement) {
+        match stmt {
+            Statement::Expression(expr) => {
+                self.expr(expr);
+            }
+            Statement::Declaration(pattern, ty, init) => {
+                let annotated = self.annotation(&ty);
+                let line = ty.as_ref().map_or(0, |t| t.line);
+                if let Some(init) = init {
+                    let actual = self.expr(init);
+                    if !self.accepts(&annotated, &actual) {
+                        let msg = format!("Declared as {} but given {}", annotated, actual);
+                        self.error(line, msg);
+                    }
+                }
+                // let (a, b): Tuple = ... annotates the tuple, not the names pulled out of it
+                let bindings = pattern.bindings();
+                let single = bindings.len() == 1 && pattern.is_catch_all();
+                for sym in bindings {
+                    let ty = if single { annotated.clone() } else { Type::Any };
+                    self.bind(&sym.name, ty, line);
+                }
+            }
+            // A const never changes so without an annotation it keeps the type of its value
+            Statement::ConstDeclaration(pattern, ty, init) => {
+                let annotated = self.annotation(&ty);
+                let line = ty.as_ref().map_or(0, |t| t.line);
+                let actual = self.expr(init);
+                if !self.accepts(&annotated, &actual) {
+                    self.error(
+                        line,
+                        format!("Declared as {} but given {}", annotated, actual),
+                    );
+                }
+                let bindings = pattern.bindings();
+                let single = bindings.len() == 1 && pattern.is_catch_all();
+                for sym in bindings {
+                    let ty = match (&annotated, single) {
+                        (Type::Any, true) => actual.clone(),
+                        (_, true) => annotated.clone(),
+                        (_, false) => Type::Any,
+                    };
+                    self.bind(&sym.name, ty, line);
+                }
+            }
+            Statement::Assignment(sym, expr) => {
+                let actual = self.expr(expr);
+                self.check_assign(&sym, &actual);
+            }
+            Statement::CompoundAssignment(sym, op, expr) => {
+                let current = self.lookup(&sym.name).map_or(Type::Any, |b| b.ty.clone());
+                let right = self.expr(expr);
+                let actual = self.binary(current, &op, right);
+                self.check_assign(&sym, &actual);
+            }
+            Statement::AssignPattern(_, expr) => {
+                self.expr(expr);
+            }
+            Statement::FuncDclaration(name, params, ret, body) => {
+                let signature = self.signature(&name.name, &params, &ret);
+                self.bind_function(&name.name, signature.clone());
+                self.function(signature, ret, params, body);
+            }
+            Statement::Return(keyword, expr) => {
+                let actual = self.expr(expr);
+                if let Some((name, ret, _)) = self.returns.last().cloned() {
+                    if !self.accepts(&ret, &actual) {
+                        let msg = format!("{} returns {} but this returns {}", name, ret, actual);
+                        self.error(keyword.line, msg);
+                    }
+                }
+            }
+            Statement::Throw(_, expr) => {
+                self.expr(expr);
+            }
+            // The names were bound along with their types in declare
+            Statement::StructDeclaration(name, _, methods)
+            | Statement::ImplDeclaration(name, methods)
+            | Statement::ClassDeclaration(name, _, methods) => self.methods(&name.name, methods),
+            Statement::EnumDeclaration(name, _) => self.bind(&name.name, Type::Any, 0),
+            Statement::SetField(object, name, expr) => {
+                let object = self.expr(object);
+                let actual = self.expr(expr);
+                if let Some(expected) = self.field(&object, &name.lex) {
+                    if !self.accepts(&expected, &actual) {
+                        let msg = format!(
+                            "Field {} of {} is {} but given {}",
+                            name.lex, object, expected, actual
+                        );
+                        self.error(name.line, msg);
+                    }
+                }
+            }
+            Statement::SetIndex(object, _, index, expr) => {
+                self.expr(object);
+                self.expr(index);
+                self.expr(expr);
+            }
+            // Fields keep their type, p.x += 1 is only checked for x + 1 making sense
+            Statement::CompoundSet(target, op, expr) => {
+                let current = self.expr(target);
+                let right = self.expr(expr);
+                self.binary(current, &op, right);
+            }
+            Statement::Import(_, sym, names) => match names {
+                Some(names) => {
+                    for name in names {
+                        self.imported.insert(name.name.clone());
+                        self.bind(&name.name, Type::Any, 0);
+                    }
+                }
+                None => self.bind(&sym.name, Type::Any, 0),
+            },
+        }
+    }
+
+    /*Reported where the assignment is, the message points back at the declaration. A name without an
+    annotation can be given anything, after that nothing is known about it */
+    fn check_assign(&mut self, sym: &Symbol, actual: &Type) {
+        let binding = self.scopes.iter_mut().rev().find_map(|s| s.get_mut(&sym.name));
+        let Some(binding) = binding else {
+            return;
+        };
+        if binding.line == 0 {
+            binding.ty = Type::Any;
+            binding.signature = None;
+            binding.structure = None;
+            return;
+        }
+        let binding = binding.clone();
+        if !self.accepts(&binding.ty, actual) {
+            let msg = format!(
+                "Cant assign {} to {} which is declared as {} on line {}",
+                actual, sym.name, binding.ty, binding.line
+            );
+            self.error(sym.line, msg);
+        }
+    }
+
+    fn methods(&mut self, struct_name: &str, methods: Vec<Statement>) {
+        self.current_struct.push(struct_name.to_string());
+        for method in methods {
+            if let Statement::FuncDclaration(name, params, ret, body) = method {
+                let full_name = format!("{}.{}", struct_name, name.name);
+                let signature = self.signature(&full_name, &params, &ret);
+                self.function(signature, ret, params, body);
+            }
+        }
+        self.current_struct.pop();
+    }
+
+    // The body is checked with the parameters bound to their annotations. Its last expression is returned too
+    fn function(
+        &mut self,
+        signature: Signature,
+        ret: Option<Token>,
+        params: Vec<Param>,
+        body: Expression,
+    ) {
+        self.scopes.push(HashMap::new());
+        for (param, (_, ty)) in params.iter().filter(|p| !p.rest).zip(&signature.params) {
+            if let Some(default) = param.default.clone() {
+                let actual = self.expr(default);
+                if !self.accepts(ty, &actual) {
+                    let msg = format!(
+                        "Default value of {} is {} but it is declared as {}",
+                        param.name.name, actual, ty
+                    );
+                    self.error(param.ty.as_ref().map_or(0, |t| t.line), msg);
+                }
+            }
+            let line = param.ty.as_ref().map_or(0, |t| t.line);
+            self.bind(&param.name.name, ty.clone(), line);
+        }
+        if let Some(rest) = params.iter().find(|p| p.rest) {
+            self.bind(&rest.name.name, Type::List, 0);
+        }
+        let line = ret.as_ref().map_or(0, |t| t.line);
+        self.returns
+            .push((signature.name.clone(), signature.ret.clone(), line));
+        let last = match body {
+            Expression::BlockExpr(stmts) => self.check_stmts(stmts),
+            body => self.expr(body),
+        };
+        if !self.accepts(&signature.ret, &last) {
+            let msg = format!(
+                "{} returns {} but its last expression is {}",
+                signature.name, signature.ret, last
+            );
+            self.error(line, msg);
+        }
+        self.returns.pop();
+        self.scopes.pop();
+    }
+
+    // The declared type of a field. None when it isn't known
+    fn field(&self, object: &Type, field: &str) -> Option<Type> {
+        let Type::Named(name) = object else {
+            return None;
+        };
+        let mut current = Some(name.clone());
+        while let Some(n) = current {
+            let s = self.struct_type(&n)?;
+            if let Some(ty) = s.fields.get(field) {
+                return Some(ty.clone());
+            }
+            current = s.superclass.clone();
+        }
+        None
+    }
+
+    fn method(&self, object: &Type, method: &str) -> Option<Signature> {
+        let name = match object {
+            Type::Named(name) => name,
+            // (0..10).step(2)
+            Type::Range if method == "step" => {
+                return Some(Signature {
+                    name: "step".to_string(),
+                    params: vec![("step".to_string(), Type::Int)],
+                    required: 1,
+                    rest: None,
+                    ret: Type::Range,
+                })
+            }
+            _ => return None,
+        };
+        let mut current = Some(name.clone());
+        while let Some(n) = current {
+            let s = self.struct_type(&n)?;
+            if let Some(signature) = s.methods.get(method) {
+                return Some(signature.clone());
+            }
+            current = s.superclass.clone();
+        }
+        None
+    }
+
+    // An if without an else, or branches of different types, could be either
+    fn join(a: Type, b: Type) -> Type {
+        if a == b {
+            a
+        } else {
+            Type::Any
+        }
+    }
+
+    fn expr(&mut self, expr: Expression) -> Type {
+        match expr {
+            Expression::Literal(lit) => match lit {
+                Literal::Str(_) => Type::String,
+                Literal::Int(_) | Literal::BigInt(_) => Type::Int,
+                Literal::Number(_) => Type::Float,
+                Literal::True | Literal::False => Type::Bool,
+                Literal::Nil => Type::Nil,
+            },
+            Expression::Grouping(expr) => self.expr(*expr),
+            Expression::Primary(sym) => match self.lookup(&sym.name) {
+                Some(binding) => binding.ty.clone(),
+                None => Type::Any,
+            },
+            Expression::Binary(left, op, right) => {
+                let left = self.expr(*left);
+                let right = self.expr(*right);
+                self.binary(left, &op, right)
+            }
+            Expression::Unary(op, expr) => {
+                let actual = self.expr(*expr);
+                let expected = match op.token_type {
+                    TokenType::Not => Type::Bool,
+                    TokenType::Tilde => Type::Int,
+                    _ => Type::Number,
+                };
+                if !self.accepts(&expected, &actual) {
+                    self.error(op.line, format!("Cant use {} on {}", op.lex, actual));
+                    return expected;
+                }
+                match (&expected, actual.is_number()) {
+                    (Type::Number, true) => actual,
+                    _ => expected,
+                }
+            }
+            Expression::Logical(left, op, right) => {
+                for side in [*left, *right] {
+                    let actual = self.expr(side);
+                    if !self.accepts(&Type::Bool, &actual) {
+                        let msg =
+                            format!("Both sides of {} have to be Bool, got {}", op.lex, actual);
+                        self.error(op.line, msg);
+                    }
+                }
+                Type::Bool
+            }
+            Expression::Ternary(condition, then, elses) => {
+                self.expr(*condition);
+                let then = self.expr(*then);
+                let elses = self.expr(*elses);
+                Checker::join(then, elses)
+            }
+            Expression::Call(callee, paren, args, named) => self.call(*callee, paren, args, named),
+            Expression::BlockExpr(stmts) => {
+                self.scopes.push(HashMap::new());
+                let last = self.check_stmts(stmts);
+                self.scopes.pop();
+                last
+            }
+            Expression::IfExpr(condition, then, elses) => {
+                self.expr(*condition);
+                let then = self.expr(*then);
+                match *elses {
+                    Some(elses) => {
+                        let elses = self.expr(elses);
+                        Checker::join(then, elses)
+                    }
+                    None => Type::Any,
+                }
+            }
+            Expression::WhileExpr(condition, body) => {
+                self.expr(*condition);
+                self.expr(*body);
+                Type::Any
+            }
+            Expression::LoopExpr(body) => {
+                self.expr(*body);
+                Type::Any
+            }
+            Expression::ForExpr(var, iterable, body) => {
+                let item = match self.expr(*iterable) {
+                    Type::Range => Type::Int,
+                    Type::String => Type::String,
+                    _ => Type::Any,
+                };
+                self.scopes.push(HashMap::new());
+                self.bind(&var.name, item, 0);
+                self.expr(*body);
+                self.scopes.pop();
+                Type::Any
+            }
+            Expression::BreakExpr | Expression::ContinueExpr => Type::Any,
+            Expression::Get(object, name) => {
+                if let Expression::Primary(sym) = object.as_ref() {
+                    if self.enums.contains(&sym.name) {
+                        return Type::Named(sym.name.clone());
+                    }
+                }
+                let object = self.expr(*object);
+                match object {
+                    Type::Range if name.lex == "step" => Type::Function,
+                    Type::Number
+                    | Type::Range
+                    | Type::Int
+                    | Type::Float
+                    | Type::String
+                    | Type::Bool
+                    | Type::Nil
+                    | Type::List => {
+                        self.error(name.line, format!("{} has no field {}", object, name.lex));
+                        Type::Any
+                    }
+                    Type::Error if name.lex == "line" => Type::Int,
+                    Type::Error => Type::String,
+                    object => match self.field(&object, &name.lex) {
+                        Some(ty) => ty,
+                        None if self.method(&object, &name.lex).is_some() => Type::Function,
+                        None => Type::Any,
+                    },
+                }
+            }
+            Expression::StructInit(sym, fields) => {
+                let info = self.struct_type(&sym.name).cloned();
+                for (field, expr) in fields {
+                    let actual = self.expr(expr);
+                    let Some(info) = &info else {
+                        continue;
+                    };
+                    match info.fields.get(&field.lex) {
+                        Some(expected) if !self.accepts(expected, &actual) => {
+                            let msg = format!(
+                                "Field {} of {} is {} but given {}",
+                                field.lex, sym.name, expected, actual
+                            );
+                            self.error(field.line, msg);
+                        }
+                        None if !info.is_class => {
+                            let msg = format!("Struct {} has no field {}", sym.name, field.lex);
+                            self.error(field.line, msg);
+                        }
+                        _ => (),
+                    }
+                }
+                Type::Named(sym.name)
+            }
+            Expression::This(_) => match self.current_struct.last() {
+                Some(name) => Type::Named(name.clone()),
+                None => Type::Any,
+            },
+            Expression::List(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+                Type::List
+            }
+            Expression::Tuple(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+                Type::Tuple
+            }
+            Expression::Dict(entries) => {
+                for (key, val) in entries {
+                    self.expr(key);
+                    self.expr(val);
+                }
+                Type::Dict
+            }
+            Expression::Interpolation(parts) => {
+                for part in parts {
+                    self.expr(part);
+                }
+                Type::String
+            }
+            Expression::Match(_, subject, arms) => {
+                self.expr(*subject);
+                let mut result = None;
+                for arm in arms {
+                    self.scopes.push(HashMap::new());
+                    for sym in arm.pattern.bindings() {
+                        self.bind(&sym.name, Type::Any, 0);
+                    }
+                    if let Some(guard) = arm.guard {
+                        self.expr(guard);
+                    }
+                    let body = self.expr(arm.body);
+                    self.scopes.pop();
+                    result = Some(match result {
+                        Some(r) => Checker::join(r, body),
+                        None => body,
+                    });
+                }
+                result.unwrap_or(Type::Any)
+            }
+            Expression::Try(body, catch, finally) => {
+                let mut result = self.expr(*body);
+                if let Some(catch) = catch {
+                    self.scopes.push(HashMap::new());
+                    if let Some(name) = &catch.name {
+                        self.bind(&name.name, Type::Any, 0);
+                    }
+                    let caught = self.expr(catch.body);
+                    self.scopes.pop();
+                    result = Checker::join(result, caught);
+                }
+                if let Some(finally) = finally {
+                    self.expr(*finally);
+                }
+                result
+            }
+            Expression::Propagate(_, expr) | Expression::Spread(expr) => {
+                self.expr(*expr);
+                Type::Any
+            }
+            Expression::Lambda(name, params, body) => {
+                let signature = self.signature(&name.name, &params, &None);
+                self.function(signature, None, params, *body);
+                Type::Function
+            }
+            Expression::Index(object, bracket, index) => {
+                let object = self.expr(*object);
+                self.expr(*index);
+                match object {
+                    Type::String => Type::String,
+                    Type::Number
+                    | Type::Int
+                    | Type::Float
+                    | Type::Bool
+                    | Type::Nil
+                    | Type::Function
+                    | Type::Range => {
+                        self.error(bracket.line, format!("Cant index into {}", object));
+                        Type::Any
+                    }
+                    _ => Type::Any,
+                }
+            }
+            Expression::Slice(object, _, start, end) => {
+                let object = self.expr(*object);
+                for bound in [start, end].into_iter().flatten() {
+                    self.expr(*bound);
+                }
+                match object {
+                    Type::List | Type::Tuple | Type::String => object,
+                    _ => Type::Any,
+                }
+            }
+            Expression::Super(_, _) => Type::Any,
+        }
+    }
+
+    // The type of left op right. Reports operands that are certain to fail
+    fn binary(&mut self, left: Type, op: &Token, right: Type) -> Type {
+        let numbers = self.accepts(&Type::Number, &left) && self.accepts(&Type::Number, &right);
+        let result = match op.token_type {
+            TokenType::Plus => match (&left, &right) {
+                (Type::String, r) if r.is_number() || *r == Type::String || r.is_any() => {
+                    Some(Type::String)
+                }
+                (l, Type::String) if l.is_number() || l.is_any() => Some(Type::String),
+                _ if numbers => Some(Checker::arithmetic(&left, op, &right)),
+                _ => None,
+            },
+            TokenType::Minus
+            | TokenType::Aster
+            | TokenType::Slash
+            | TokenType::SlashSlash
+            | TokenType::Percent
+            | TokenType::AsterAster => numbers.then(|| Checker::arithmetic(&left, op, &right)),
+            TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual => numbers.then_some(Type::Bool),
+            TokenType::Ampersand
+            | TokenType::Pipe
+            | TokenType::Caret
+            | TokenType::LessLess
+            | TokenType::GreaterGreater => {
+                let ints = self.accepts(&Type::Int, &left) && self.accepts(&Type::Int, &right);
+                ints.then_some(Type::Int)
+            }
+            TokenType::DotDot | TokenType::DotDotEqual => {
+                let ints = self.accepts(&Type::Int, &left) && self.accepts(&Type::Int, &right);
+                ints.then_some(Type::Range)
+            }
+            // Strings can only hold strings, the other containers can hold anything
+            TokenType::In => match (&left, &right) {
+                (l, Type::String) if *l != Type::String && !l.is_any() => None,
+                (
+                    _,
+                    Type::Range | Type::List | Type::Tuple | Type::Dict | Type::String | Type::Any,
+                ) => Some(Type::Bool),
+                _ => None,
+            },
+            TokenType::Equality | TokenType::NotEqual => Some(Type::Bool),
+            _ => Some(Type::Any),
+        };
+        match result {
+            Some(ty) => ty,
+            None => {
+                let msg = format!("Cant use {} on {} and {}", op.lex, left, right);
+                self.error(op.line, msg);
+                Type::Any
+            }
+        }
+    }
+
+    /*Two Ints make an Int except with / which always makes a Float. ** makes a Float when the power is negative
+    so it could be either. Anything with a Float in it is a Float */
+    fn arithmetic(left: &Type, op: &Token, right: &Type) -> Type {
+        match (left, right) {
+            (Type::Any, _) | (_, Type::Any) => Type::Any,
+            (Type::Int, Type::Int) => match op.token_type {
+                TokenType::Slash => Type::Float,
+                TokenType::AsterAster => Type::Number,
+                _ => Type::Int,
+            },
+            (Type::Float, _) | (_, Type::Float) => Type::Float,
+            _ => Type::Number,
+        }
+    }
+
+    fn call(
+        &mut self,
+        callee: Expression,
+        paren: Token,
+        args: Vec<Expression>,
+        named: Vec<(Token, Expression)>,
+    ) -> Type {
+        let mut spread = false;
+        let mut positional = vec![];
+        for arg in args {
+            if matches!(arg, Expression::Spread(_)) {
+                spread = true;
+            }
+            positional.push(self.expr(arg));
+        }
+        let named: Vec<(Token, Type)> = named.into_iter().map(|(n, e)| (n, self.expr(e))).collect();
+
+        // Calling a class makes an instance. Shape.Circle(1) makes a Shape
+        let (signature, result) = match &callee {
+            Expression::Primary(sym) if self.struct_type(&sym.name).is_some_and(|s| s.is_class) => {
+                let class = Type::Named(sym.name.clone());
+                (self.method(&class, "init"), Some(class))
+            }
+            Expression::Get(object, _) if matches!(object.as_ref(), Expression::Primary(s) if self.enums.contains(&s.name)) =>
+            {
+                self.expr(callee.clone());
+                let Expression::Primary(sym) = object.as_ref() else {
+                    unreachable!()
+                };
+                (None, Some(Type::Named(sym.name.clone())))
+            }
+            Expression::Primary(sym) => match self.lookup(&sym.name) {
+                Some(binding) => (binding.signature.clone(), None),
+                None => (None, None),
+            },
+            Expression::Get(object, name) => {
+                let object = self.expr(*object.clone());
+                (self.method(&object, &name.lex), None)
+            }
+            callee => {
+                self.expr(callee.clone());
+                (None, None)
+            }
+        };
+        let Some(signature) = signature else {
+            return result.unwrap_or(Type::Any);
+        };
+        self.arguments(&signature, &paren, &positional, &named, spread);
+        result.unwrap_or(signature.ret)
+    }
+
+    // Mirrors function::arrange_args, the counts can only be checked when nothing is spread into the call
+    fn arguments(
+        &mut self,
+        signature: &Signature,
+        paren: &Token,
+        positional: &[Type],
+        named: &[(Token, Type)],
+        spread: bool,
+    ) {
+        let name = &signature.name;
+        let max = signature.params.len();
+        if !spread && signature.rest.is_none() && positional.len() > max {
+            let msg = match signature.required == max {
+                true => format!(
+                    "{} takes {} arguments but got {}",
+                    name,
+                    max,
+                    positional.len()
+                ),
+                false => format!(
+                    "{} takes at most {} arguments but got {}",
+                    name,
+                    max,
+                    positional.len()
+                ),
+            };
+            self.error(paren.line, msg);
+        }
+        for (arg, _) in named {
+            if !signature.params.iter().any(|(p, _)| *p == arg.lex) {
+                self.error(
+                    arg.line,
+                    format!("{} has no parameter named {}", name, arg.lex),
+                );
+            }
+        }
+        if !spread {
+            let missing: Vec<String> = signature.params[..signature.required]
+                .iter()
+                .enumerate()
+                .filter(|(i, (p, _))| {
+                    *i >= positional.len() && !named.iter().any(|(n, _)| n.lex == *p)
+                })
+                .map(|(i, (p, _))| {
+                    if p.is_empty() {
+                        format!("#{}", i + 1)
+                    } else {
+                        p.clone()
+                    }
+                })
+                .collect();
+            if !missing.is_empty() {
+                let msg = format!("{} is missing arguments: {}", name, missing.join(", "));
+                self.error(paren.line, msg);
+            }
+        }
+        if spread {
+            return;
+        }
+        for (i, actual) in positional.iter().enumerate() {
+            let expected = match signature.params.get(i) {
+                Some((_, ty)) => ty,
+                None => match &signature.rest {
+                    Some(ty) => ty,
+                    None => break,
+                },
+            };
+            if !self.accepts(expected, actual) {
+                let msg = format!(
+                    "Argument {} of {} should be {} but got {}",
+                    i + 1,
+                    name,
+                    expected,
+                    actual
+                );
+                self.error(paren.line, msg);
+            }
+        }
+        for (arg, actual) in named {
+            if let Some((_, expected)) = signature.params.iter().find(|(p, _)| *p == arg.lex) {
+                if !self.accepts(expected, actual) {
+                    let msg = format!(
+                        "Argument {} of {} should be {} but got {}",
+                        arg.lex, name, expected, actual
+                    );
+                    self.error(arg.line, msg);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::{collections::HashMap, fmt::Debug};
+
+use crate::{error::ErrorValue, interpreter::Value};
+
+/*Dictionary that remembers the order keys were inserted in. Entries hold the keys and values in order
+and index maps each key to its position in entries. */
+#[derive(Clone, Default)]
+pub struct Dict {
+    entries: Vec<(Value, Value)>,
+    index: HashMap<Value, usize>,
+}
+
+impl Dict {
+    pub fn new() -> Dict {
+        Dict::default()
+    }
+
+    // Only strings, numbers, bools, nil and tuples of them can be keys
+    pub fn is_key(key: &Value) -> bool {
+        match key {
+            Value::Number(n) => !n.is_nan(),
+            Value::Int(_) | Value::BigInt(_) | Value::String(_) | Value::Bool(_) | Value::Nil => true,
+            Value::Tuple(t) => t.iter().all(Dict::is_key),
+            Value::Variant(v) => v.values.iter().all(Dict::is_key),
+            _ => false,
+        }
+    }
+
+    pub fn get(&self, key: &Value) -> Option<Value> {
+        self.index.get(key).map(|i| self.entries[*i].1.clone())
+    }
+
+    pub fn contains(&self, key: &Value) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: Value, val: Value) -> Result<(), ErrorValue> {
+        if !Dict::is_key(&key) {
+            return Err(ErrorValue::new(
+                "TypeError",
+                format!("{} cant be used as a dictionary key", key),
+                0,
+            ));
+        }
+        match self.index.get(&key) {
+            Some(i) => self.entries[*i].1 = val,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, val));
+            }
+        }
+        Ok(())
+    }
+
+    // Removing shifts every later entry down by one so their positions in the index are updated
+    pub fn remove(&mut self, key: &Value) -> Option<Value> {
+        let i = self.index.remove(key)?;
+        let (_, val) = self.entries.remove(i);
+        for (k, _) in &self.entries[i..] {
+            if let Some(pos) = self.index.get_mut(k) {
+                *pos -= 1;
+            }
+        }
+        Some(val)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn keys(&self) -> Vec<Value> {
+        self.entries.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    pub fn values(&self) -> Vec<Value> {
+        self.entries.iter().map(|(_, v)| v.clone()).collect()
+    }
+}
+
+// Two dictionaries are equal when they hold the same keys and values, the order doesn't matter
+impl PartialEq for Dict {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .entries
+                .iter()
+                .all(|(k, v)| other.get(k).is_some_and(|o| o == *v))
+    }
+}
+
+impl Debug for Dict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+impl std::fmt::Display for Dict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.entries.is_empty() {
+            return write!(f, "{{:}}");
+        }
+        write!(f, "{{")?;
+        for (i, (k, v)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", k, v)?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,128 @@
+use std::{fmt::Debug, ops::RangeInclusive, rc::Rc};
+
+use crate::{
+    error::ErrorValue,
+    function::Callable,
+    interpreter::{Interpreter, Value},
+};
+
+/*Created by an enum declaration. enum Shape { Circle(r), Rect(w, h), Empty }
+Each variant has the names of the values it carries, Empty carries none */
+pub struct EnumDef {
+    pub name: String,
+    pub variants: Vec<(String, Vec<String>)>,
+}
+
+impl EnumDef {
+    pub fn variant(&self, name: &str) -> Option<usize> {
+        self.variants.iter().position(|(v, _)| v == name)
+    }
+}
+
+impl Debug for EnumDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnumDef")
+            .field("name", &self.name)
+            .field("variants", &self.variants)
+            .finish()
+    }
+}
+
+// A value of an enum. Shape.Circle(2) Variants can't be changed after they are made
+pub struct Variant {
+    pub def: Rc<EnumDef>,
+    pub index: usize,
+    pub values: Vec<Value>,
+}
+
+impl Variant {
+    pub fn name(&self) -> &str {
+        &self.def.variants[self.index].0
+    }
+
+    pub fn get(&self, field: &str) -> Result<Value, ErrorValue> {
+        let fields = &self.def.variants[self.index].1;
+        match fields.iter().position(|f| f == field) {
+            Some(i) => Ok(self.values[i].clone()),
+            None => Err(ErrorValue::new(
+                "AttributeError",
+                format!("{} has no field {}", self.name(), field),
+                0,
+            )),
+        }
+    }
+}
+
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.def, &other.def)
+            && self.index == other.index
+            && self.values == other.values
+    }
+}
+
+impl Debug for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Variant")
+            .field("enum", &self.def.name)
+            .field("name", &self.name())
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())?;
+        if self.values.is_empty() {
+            return Ok(());
+        }
+        write!(f, "(")?;
+        for (i, v) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", v)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// Shape.Circle on its own. Calling it makes the variant
+#[derive(Clone)]
+pub struct VariantConstructor {
+    pub def: Rc<EnumDef>,
+    pub index: usize,
+}
+
+impl Debug for VariantConstructor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VariantConstructor")
+            .field("enum", &self.def.name)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl Callable for VariantConstructor {
+    fn name(&self) -> String {
+        format!("{}.{}", self.def.name, self.def.variants[self.index].0)
+    }
+
+    fn arity(&self) -> RangeInclusive<usize> {
+        let n = self.def.variants[self.index].1.len();
+        n..=n
+    }
+
+    fn params(&self) -> Vec<String> {
+        self.def.variants[self.index].1.clone()
+    }
+
+    fn call(&mut self, _: &mut Interpreter, args: &[Option<Value>]) -> Result<Value, ErrorValue> {
+        Ok(Value::Variant(Rc::new(Variant {
+            def: self.def.clone(),
+            index: self.index,
+            values: args.iter().flatten().cloned().collect(),
+        })))
+    }
+}
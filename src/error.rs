@@ -0,0 +1,77 @@
+use std::fmt::Debug;
+
+use crate::interpreter::Value;
+
+/*A runtime error. It is what the interpreter passes back on the Err path and what a catch block gets.
+1 / 0 // [1][5] // -"a". Errors are made with line 0 where the line isn't known and the nearest caller
+that knows it fills it in. error("x") makes one by hand with the kind Error */
+#[derive(Clone)]
+pub struct ErrorValue {
+    pub kind: String,
+    pub message: String,
+    pub line: usize,
+}
+
+impl ErrorValue {
+    pub fn new(kind: &str, message: impl Into<String>, line: usize) -> ErrorValue {
+        ErrorValue {
+            kind: kind.to_string(),
+            message: message.into(),
+            line,
+        }
+    }
+
+    // The first line given is where it happened, callers further out can't move it
+    pub fn at(mut self, line: usize) -> ErrorValue {
+        if self.line == 0 {
+            self.line = line;
+        }
+        self
+    }
+
+    // How an error nothing caught is shown. @Line 3: TypeError: Cant negate a
+    pub fn report(&self) -> String {
+        match self.line {
+            0 => self.to_string(),
+            line => format!("@Line {}: {}", line, self),
+        }
+    }
+
+    pub fn get(&self, field: &str) -> Result<Value, ErrorValue> {
+        match field {
+            "kind" => Ok(Value::String(self.kind.clone())),
+            "message" => Ok(Value::String(self.message.clone())),
+            "line" => Ok(Value::Int(self.line as i64)),
+            _ => Err(ErrorValue::new(
+                "AttributeError",
+                format!(
+                    "Errors have no field {}. Only kind, message and line",
+                    field
+                ),
+                0,
+            )),
+        }
+    }
+}
+
+impl PartialEq for ErrorValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.message == other.message && self.line == other.line
+    }
+}
+
+impl Debug for ErrorValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorValue")
+            .field("kind", &self.kind)
+            .field("message", &self.message)
+            .field("line", &self.line)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for ErrorValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
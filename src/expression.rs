@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
 use crate::{
+    pattern::Pattern,
     statement::Statement,
     token::{Literal, Token},
 };
@@ -15,7 +16,7 @@ pub enum Expression {
     Ternary(Box<Expression>, Box<Expression>, Box<Expression>),
     // Assignment(Symbol, Box<Expression>),
     Primary(Symbol),                               //Variable
-    Call(Box<Expression>, Token, Vec<Expression>), //Callee, args
+    Call(Box<Expression>, Token, Vec<Expression>, Vec<(Token, Expression)>), //Callee, args, keyword args
     BlockExpr(Vec<Statement>),
     IfExpr(
         Box<Expression>,
@@ -24,8 +25,29 @@ pub enum Expression {
     ),
     LoopExpr(Box<Expression>),
     WhileExpr(Box<Expression>, Box<Expression>),
+    ForExpr(Symbol, Box<Expression>, Box<Expression>), //Loop variable, iterable, body
     BreakExpr,
     ContinueExpr,
+    Get(Box<Expression>, Token),                   //Object, field name
+    StructInit(Symbol, Vec<(Token, Expression)>),  //Struct name, (field name, value)
+    This(Symbol),
+    List(Vec<Expression>),                         // [1, 2, 3]
+    Tuple(Vec<Expression>),                        // (1, 2)
+    Interpolation(Vec<Expression>),                // "a {b} c"
+    Lambda(Symbol, Vec<Param>, Box<Expression>),   // fun (x) { x * 2 } // |x| x * 2
+    Spread(Box<Expression>),                       // f(..xs) Only allowed as a call argument
+    Match(Token, Box<Expression>, Vec<MatchArm>),  // match x { 0 => "zero", _ => "other" }
+    Try(Box<Expression>, Option<Box<Catch>>, Option<Box<Expression>>), // try { } catch e { } finally { }
+    Propagate(Token, Box<Expression>), // f()? Unwraps an Ok or returns the Err from the function
+    Dict(Vec<(Expression, Expression)>),           // {"a": 1, "b": 2}
+    Index(Box<Expression>, Token, Box<Expression>), //Object, [, index
+    Slice(
+        Box<Expression>,
+        Token,
+        Option<Box<Expression>>,
+        Option<Box<Expression>>,
+    ), //Object, [, start, end
+    Super(Symbol, Token), // super.method
 }
 
 impl Debug for Expression {
@@ -53,11 +75,12 @@ impl Debug for Expression {
                 .field(arg2)
                 .finish(),
             Self::Primary(arg0) => f.debug_tuple("Primary").field(arg0).finish(),
-            Self::Call(arg0, arg1, arg2) => f
+            Self::Call(arg0, arg1, arg2, arg3) => f
                 .debug_tuple("Call")
                 .field(arg0)
                 .field(arg1)
                 .field(arg2)
+                .field(arg3)
                 .finish(),
             Self::BlockExpr(arg0) => f.debug_tuple("BlockExpr").field(arg0).finish(),
             Self::IfExpr(arg0, arg1, arg2) => f
@@ -70,8 +93,57 @@ impl Debug for Expression {
             Self::WhileExpr(arg0, arg1) => {
                 f.debug_tuple("WhileExpr").field(arg0).field(arg1).finish()
             }
+            Self::ForExpr(arg0, arg1, arg2) => f
+                .debug_tuple("ForExpr")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
             Self::BreakExpr => write!(f, "BreakExpr"),
             Self::ContinueExpr => write!(f, "ContinueExpr"),
+            Self::Get(arg0, arg1) => f.debug_tuple("Get").field(arg0).field(arg1).finish(),
+            Self::StructInit(arg0, arg1) => {
+                f.debug_tuple("StructInit").field(arg0).field(arg1).finish()
+            }
+            Self::This(arg0) => f.debug_tuple("This").field(arg0).finish(),
+            Self::List(arg0) => f.debug_tuple("List").field(arg0).finish(),
+            Self::Tuple(arg0) => f.debug_tuple("Tuple").field(arg0).finish(),
+            Self::Interpolation(arg0) => f.debug_tuple("Interpolation").field(arg0).finish(),
+            Self::Spread(arg0) => f.debug_tuple("Spread").field(arg0).finish(),
+            Self::Match(arg0, arg1, arg2) => f
+                .debug_tuple("Match")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::Try(arg0, arg1, arg2) => f
+                .debug_tuple("Try")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::Propagate(arg0, arg1) => f.debug_tuple("Propagate").field(arg0).field(arg1).finish(),
+            Self::Lambda(arg0, arg1, arg2) => f
+                .debug_tuple("Lambda")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::Dict(arg0) => f.debug_tuple("Dict").field(arg0).finish(),
+            Self::Index(arg0, arg1, arg2) => f
+                .debug_tuple("Index")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::Slice(arg0, arg1, arg2, arg3) => f
+                .debug_tuple("Slice")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .field(arg3)
+                .finish(),
+            Self::Super(arg0, arg1) => f.debug_tuple("Super").field(arg0).field(arg1).finish(),
         }
     }
 }
@@ -80,6 +152,7 @@ impl Debug for Expression {
 pub struct Symbol {
     pub name: String,
     pub s_id: u64,
+    pub line: usize,
 }
 
 impl Debug for Symbol {
@@ -87,3 +160,58 @@ impl Debug for Symbol {
         f.debug_struct("Symbol").field("name", &self.name).finish()
     }
 }
+
+// pattern if guard => body
+#[derive(Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Expression,
+}
+
+impl Debug for MatchArm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MatchArm")
+            .field("pattern", &self.pattern)
+            .field("guard", &self.guard)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+// catch e { } The name can be left out when the error isn't needed
+#[derive(Clone)]
+pub struct Catch {
+    pub name: Option<Symbol>,
+    pub body: Expression,
+}
+
+impl Debug for Catch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Catch")
+            .field("name", &self.name)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+/*A function parameter. The default is evaluated each time the function is called without it.
+A rest parameter (..parts) collects the extra arguments into a list */
+#[derive(Clone)]
+pub struct Param {
+    pub name: Symbol,
+    pub default: Option<Expression>,
+    pub rest: bool,
+    pub ty: Option<Token>, // x: Number
+}
+
+impl Debug for Param {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Param")
+            .field("name", &self.name)
+            .field("default", &self.default)
+            .field("rest", &self.rest)
+            .field("ty", &self.ty)
+            .finish()
+    }
+}
@@ -1,24 +1,27 @@
 use crate::{
-    expression::Symbol,
+    error::ErrorValue,
+    expression::{Param, Symbol},
     interpreter::{Interpreter, Value},
     scope::Scope,
     statement::Statement,
+    token::Token,
 };
-use std::{fmt::Debug, collections::HashMap};
+use std::{cell::RefCell, fmt::Debug, ops::RangeInclusive, rc::Rc};
 
 #[derive(Clone)]
 pub struct Function {
     pub name: Symbol,
-    pub params: Vec<Symbol>,
+    pub params: Vec<Param>,
     pub body: Vec<Statement>,
     pub closure: Scope,
     pub f_id: u64,
+    pub method: bool, // Declared inside a struct or impl block
 }
 
 impl Function {
     pub fn new(
         name: Symbol,
-        params: Vec<Symbol>,
+        params: Vec<Param>,
         body: Vec<Statement>,
         closure: Scope,
         f_id: u64,
@@ -29,64 +32,117 @@ impl Function {
             body,
             closure,
             f_id,
+            method: false,
         }
     }
-}
-
-impl Callable for Function {
-    fn arity(&self) -> usize {
-        self.params.len()
-    }
-
-    fn call(&mut self, interpreter: &mut Interpreter, args: &[Value]) -> Result<Value, String> {
-        // println!(
-        //     "Calling {} with f_id {} with closure {:?}",
-        //     self.name.name, self.f_id, self.closure.values
-        // );
-        let mut last = Ok(Value::Nil);
 
-        let args_map : HashMap<_,_> = self.params.iter().zip(args.iter()).map(|(param,arg)|{
-            (
-                param.name.clone(),
-                    arg.clone(),
-            )
-        }).collect();
+    /*Runs the body of the function. Methods get an extra scope between their closure and their parameters
+    that holds `this`. When a method is called without a receiver (Point.new()) `this` is nil. */
+    pub fn invoke(
+        &mut self,
+        interpreter: &mut Interpreter,
+        args: &[Option<Value>],
+        this: Option<Value>,
+    ) -> Result<Value, ErrorValue> {
+        let mut last = Value::Nil;
 
-        //Clone old scope so we can return to it later.
-        let mut old_scope = interpreter.program_scope.clone();
-        //Create new scope by cloning this functions saved closure\\
-
-        let mut func_scope = self.closure.clone();
-
-        func_scope.values.extend(args_map);
+        let mut closure = self.closure.clone();
+        if self.method {
+            closure = Scope::new(Some(Box::new(closure)));
+            closure.define_var(
+                Symbol {
+                    name: "this".to_string(),
+                    s_id: 0,
+                    line: 0,
+                },
+                this.unwrap_or(Value::Nil),
+            );
+        }
 
-        old_scope.values.insert(self.name.name.clone(), Value::Function(self.f_id));
-        old_scope.values.extend(self.closure.values.clone());
-        func_scope.enclosing = Some(Box::new(old_scope.clone()));
+        //Create new scope for the parameters inside this functions saved closure
+        let func_scope = Scope::new(Some(Box::new(closure)));
 
-        //Move interpreter to new scope
-        interpreter.program_scope = func_scope.clone();
+        //Save old scope so we can return to it later and move interpreter to new scope
+        let old_scope = std::mem::replace(&mut interpreter.program_scope, func_scope);
 
-        // println!("{:?}", interpreter.program_scope.values);
-        for (_, stmt) in self.body.clone().into_iter().enumerate() {
-            if let Some(_) = interpreter.return_val.clone() {
+        let mut result = self.bind_params(interpreter, args);
+        for stmt in self.body.clone() {
+            if result.is_err() || interpreter.return_val.is_some() {
+                break;
+            }
+            result = match stmt {
+                Statement::Expression(e) => interpreter.interp_expression(e).map(|v| last = v),
+                _ => interpreter.interp_statement(stmt).map(|_| last = Value::Nil),
+            };
+            if result.is_err() {
                 break;
             }
-            match stmt {
-                Statement::Expression(e) => last = interpreter.interp_expression(e),
-                _ => _ = interpreter.interp_statement(stmt),
+        }
+        interpreter.program_scope = old_scope; // Set interpreter back to old scope
+        match result {
+            // An Err(e)? returning early
+            Err(_) if interpreter.return_val.is_some() => Ok(Value::Nil),
+            result => result.map(|_| last),
+        }
+    }
+
+    /*Defines the parameters in the functions scope. Parameters are bound in order so a default value
+    can use the ones before it. fun f(a, b = a * 2). The rest parameter gets a list of whatever is left over */
+    fn bind_params(&self, interpreter: &mut Interpreter, args: &[Option<Value>]) -> Result<(), ErrorValue> {
+        for (i, param) in self.params.iter().enumerate() {
+            if param.rest {
+                let rest = args.get(i..).unwrap_or(&[]).iter().flatten().cloned().collect();
+                let rest = Value::List(Rc::new(RefCell::new(rest)));
+                interpreter.program_scope.define_var(param.name.clone(), rest);
+                break;
             }
+            let val = match (args.get(i).cloned().flatten(), &param.default) {
+                (Some(v), _) => v,
+                (None, Some(default)) => interpreter.interp_expression(default.clone())?,
+                (None, None) => {
+                    return Err(ErrorValue::new(
+                        "TypeError",
+                        format!("{} is missing argument {}", self.name.name, param.name.name),
+                        0,
+                    ))
+                }
+            };
+            interpreter.program_scope.define_var(param.name.clone(), val);
         }
-        // println!("After function call the scope looks like {:?}",interpreter.program_scope.enclosing.clone().expect("").values);
-        //Update closure
-        self.closure = *interpreter.program_scope.enclosing.clone().expect("");
-        
-        //Update reference to function in interpreter. This assures that the function is called with the updated closure next time its called.
-        interpreter.function_map.insert(self.f_id, self.clone());
-        // old_scope.assign_var(&self.name, Value::Function(self.clone()));
-        interpreter.program_scope = old_scope.clone(); // Set interpreter back to old scope
-        // println!("AT END OF FUNCTION PROGRAM SCOPE IS EQUAL TO {:?}", interpreter.program_scope.values);
-        return last;
+        Ok(())
+    }
+}
+
+impl Callable for Function {
+    fn name(&self) -> String {
+        self.name.name.clone()
+    }
+
+    /*Parameters with a default value and the rest parameter always come last so everything before them
+    is required. A rest parameter takes any number of arguments */
+    fn arity(&self) -> RangeInclusive<usize> {
+        let required = self
+            .params
+            .iter()
+            .take_while(|p| p.default.is_none() && !p.rest)
+            .count();
+        match self.params.last() {
+            Some(p) if p.rest => required..=usize::MAX,
+            _ => required..=self.params.len(),
+        }
+    }
+
+    // The rest parameter cant be given by name
+    fn params(&self) -> Vec<String> {
+        self.params
+            .iter()
+            .filter(|p| !p.rest)
+            .map(|p| p.name.name.clone())
+            .collect()
+    }
+
+    fn call(&mut self, interpreter: &mut Interpreter, args: &[Option<Value>]) -> Result<Value, ErrorValue> {
+        self.invoke(interpreter, args, None)
     }
 }
 
@@ -94,6 +150,7 @@ impl Debug for Function {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Function")
             .field("name", &self.name)
+            .field("f_id", &self.f_id)
             .field("params", &self.params)
             .field("body", &self.body)
             .finish()
@@ -103,8 +160,8 @@ impl Debug for Function {
 #[derive(Clone)]
 pub struct NativeFunction {
     pub name: String,
-    pub arity: usize,
-    pub callable: fn(&mut Interpreter, &[Value]) -> Result<Value, String>,
+    pub arity: RangeInclusive<usize>, // usize::MAX when it takes any number of arguments
+    pub callable: fn(&mut Interpreter, &[Value]) -> Result<Value, ErrorValue>,
 }
 
 impl Debug for NativeFunction {
@@ -117,16 +174,163 @@ impl Debug for NativeFunction {
 }
 
 impl Callable for NativeFunction {
-    fn arity(&self) -> usize {
-        self.arity
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn arity(&self) -> RangeInclusive<usize> {
+        self.arity.clone()
     }
 
-    fn call(&mut self, interpreter: &mut Interpreter, args: &[Value]) -> Result<Value, String> {
-        return (self.callable)(interpreter, args);
+    // Natives have no defaults so by the time they're called every argument is there
+    fn call(&mut self, interpreter: &mut Interpreter, args: &[Option<Value>]) -> Result<Value, ErrorValue> {
+        let args: Vec<Value> = args.iter().flatten().cloned().collect();
+        return (self.callable)(interpreter, &args);
+    }
+}
+
+// A method together with the value it was accessed on. p.len evaluates to one of these
+#[derive(Clone)]
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub f_id: u64,
+    pub arity: RangeInclusive<usize>,
+    pub params: Vec<String>,
+    pub name: String,
+}
+
+impl BoundMethod {
+    pub fn new(receiver: Value, name: String, method: &Function) -> BoundMethod {
+        BoundMethod {
+            receiver,
+            f_id: method.f_id,
+            arity: method.arity(),
+            params: method.params(),
+            name,
+        }
+    }
+}
+
+impl Debug for BoundMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoundMethod")
+            .field("receiver", &self.receiver)
+            .field("name", &self.name)
+            .field("f_id", &self.f_id)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+impl Callable for BoundMethod {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn arity(&self) -> RangeInclusive<usize> {
+        self.arity.clone()
+    }
+
+    fn params(&self) -> Vec<String> {
+        self.params.clone()
+    }
+
+    fn call(&mut self, interpreter: &mut Interpreter, args: &[Option<Value>]) -> Result<Value, ErrorValue> {
+        let mut method = match interpreter.function_map.get(&self.f_id) {
+            Some(f) => f.clone(),
+            None => return Err(ErrorValue::new("RuntimeError", "Bound method no longer exists", 0)),
+        };
+        method.invoke(interpreter, args, Some(self.receiver.clone()))
+    }
+}
+
+// A native together with the builtin value it was accessed on. (0..10).step gives one of these
+#[derive(Clone)]
+pub struct NativeMethod {
+    pub receiver: Value,
+    pub native: NativeFunction,
+}
+
+impl Debug for NativeMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeMethod")
+            .field("receiver", &self.receiver)
+            .field("name", &self.native.name)
+            .finish()
+    }
+}
+
+impl Callable for NativeMethod {
+    fn name(&self) -> String {
+        self.native.name.clone()
+    }
+
+    fn arity(&self) -> RangeInclusive<usize> {
+        self.native.arity.clone()
+    }
+
+    // The receiver is passed in as the first argument
+    fn call(&mut self, interpreter: &mut Interpreter, args: &[Option<Value>]) -> Result<Value, ErrorValue> {
+        let mut all = vec![self.receiver.clone()];
+        all.extend(args.iter().flatten().cloned());
+        (self.native.callable)(interpreter, &all)
     }
 }
 
 pub trait Callable {
-    fn arity(&self) -> usize;
-    fn call(&mut self, interpreter: &mut Interpreter, args: &[Value]) -> Result<Value, String>;
+    fn name(&self) -> String;
+    fn arity(&self) -> RangeInclusive<usize>; // Fewest and most arguments it can be called with
+    // Names of the parameters for keyword arguments. Natives don't have any
+    fn params(&self) -> Vec<String> {
+        vec![]
+    }
+    // None stands for a parameter that was left out and takes its default value
+    fn call(&mut self, interpreter: &mut Interpreter, args: &[Option<Value>]) -> Result<Value, ErrorValue>;
+}
+
+/*Lines up positional and keyword arguments with the parameters of f. Positional arguments fill the
+first parameters and keyword arguments go to the parameter with their name. Anything left out has to have a default */
+pub fn arrange_args(
+    f: &dyn Callable,
+    args: Vec<Value>,
+    named: Vec<(Token, Value)>,
+) -> Result<Vec<Option<Value>>, ErrorValue> {
+    let arity = f.arity();
+    let wrong = |message: String| ErrorValue::new("TypeError", message, 0);
+    if args.len() > *arity.end() {
+        return Err(wrong(if arity.start() == arity.end() {
+            format!("{} takes {} arguments but got {}", f.name(), arity.end(), args.len())
+        } else {
+            format!("{} takes at most {} arguments but got {}", f.name(), arity.end(), args.len())
+        }));
+    }
+    let params = f.params();
+    let mut arranged: Vec<Option<Value>> = args.into_iter().map(Some).collect();
+    for (name, val) in named {
+        let i = match params.iter().position(|p| *p == name.lex) {
+            Some(i) => i,
+            None => return Err(wrong(format!("{} has no parameter named {}", f.name(), name.lex))),
+        };
+        if arranged.len() <= i {
+            arranged.resize(i + 1, None);
+        }
+        if arranged[i].is_some() {
+            return Err(wrong(format!("{} got more than one value for {}", f.name(), name.lex)));
+        }
+        arranged[i] = Some(val);
+    }
+    let missing: Vec<usize> = (0..*arity.start())
+        .filter(|i| !matches!(arranged.get(*i), Some(Some(_))))
+        .collect();
+    if !missing.is_empty() {
+        return Err(wrong(if params.is_empty() && arity.start() == arity.end() {
+            format!("{} takes {} arguments but got {}", f.name(), arity.start(), arranged.len())
+        } else if params.is_empty() {
+            format!("{} takes at least {} arguments but got {}", f.name(), arity.start(), arranged.len())
+        } else {
+            let names: Vec<&str> = missing.iter().map(|i| params[*i].as_str()).collect();
+            format!("{} is missing arguments: {}", f.name(), names.join(", "))
+        }));
+    }
+    Ok(arranged)
 }
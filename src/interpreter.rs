@@ -1,14 +1,28 @@
 use core::panic;
 use std::{
+    cell::RefCell,
+    cmp::Ordering,
     collections::HashMap,
     fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    rc::Rc,
 };
 
 use crate::{
-    expression::{Expression, Symbol},
-    function::{Callable, Function, NativeFunction},
+    bigint::BigInt,
+    dict::Dict,
+    enumeration::{EnumDef, Variant, VariantConstructor},
+    error::ErrorValue,
+    expression::{Catch, Expression, MatchArm, Param, Symbol},
+    function::{arrange_args, BoundMethod, Callable, Function, NativeFunction, NativeMethod},
+    list,
+    module::{LoadedModule, Module},
+    pattern::Pattern,
+    range::Range,
     scope::Scope,
     statement::Statement,
+    structure::{StructDef, StructInstance},
     token::{Literal, Token, TokenType},
 };
 
@@ -16,26 +30,62 @@ use crate::{
 pub struct Interpreter {
     pub program_scope: Scope,      //Scope currently being used by interpreter
     pub return_val: Option<Value>, //Current return value
-    /*Hash Map of all user defined functions
-    This is used to get closures to work. Each function has an id, when the function is called it
-    changes the value its id correlates to in this map.  */
+    /*Hash Map of all user defined functions and methods
+    Each function has an id, function values only hold this id. */
     pub function_map: HashMap<u64, Function>,
     pub f_count: u64, //Counter for next function id.
     pub global: Scope,
     pub lex_scope : HashMap<u64, usize>,
+    /*The value of a throw while it unwinds. The Err string only says where it came from,
+    catch takes the value from here. Errors raised by the interpreter don't set it */
+    pub thrown: Option<Value>,
+    pub result: Rc<EnumDef>, // The built in Result enum. Ok(v) // Err(e)
+    pub imports: HashMap<u64, Rc<Module>>, // Filled in by the resolver. Which module each import brings in
+    pub modules: HashMap<PathBuf, Rc<LoadedModule>>, // Modules that have already run
 }
 
 impl Interpreter {
     pub fn new(lex_scope : HashMap<u64, usize>) -> Interpreter {
         let mut global_map: HashMap<String, Value> = HashMap::new();
+        let result = Rc::new(EnumDef {
+            name: "Result".to_string(),
+            variants: vec![
+                ("Ok".to_string(), vec!["value".to_string()]),
+                ("Err".to_string(), vec!["error".to_string()]),
+            ],
+        });
+        global_map.insert("Result".to_string(), Value::Enum(result.clone()));
+        for (index, name) in ["Ok", "Err"].into_iter().enumerate() {
+            global_map.insert(
+                name.to_string(),
+                Value::VariantConstructor(Rc::new(VariantConstructor {
+                    def: result.clone(),
+                    index,
+                })),
+            );
+        }
         global_map.insert(
             "pow".to_string(),
             Value::NativeFunction(NativeFunction {
                 name: "pow".to_string(),
-                arity: 2,
-                callable: |_, args| match (args[0].clone(), args[1].clone()) {
-                    (Value::Number(base), Value::Number(pow)) => Ok(Value::Number(base.powf(pow))),
-                    (_, _) => panic!("Pow function can only take numbers as arguments"),
+                arity: 2..=2,
+                callable: |interp, args| match (args[0].as_bigint(), args[1].as_bigint()) {
+                    (Some(base), Some(pow)) if !pow.is_negative() => match int_pow(&base, &pow) {
+                        Some(n) => Ok(interp.ok(n)),
+                        None => Ok(interp.err(Value::Error(Rc::new(ErrorValue::new(
+                            "OverflowError",
+                            format!("{} ** {} is too big to work out", base, pow),
+                            0,
+                        ))))),
+                    },
+                    _ => match (args[0].as_f64(), args[1].as_f64()) {
+                        (Some(base), Some(pow)) => Ok(interp.ok(Value::Number(base.powf(pow)))),
+                        _ => Ok(interp.err(Value::Error(Rc::new(ErrorValue::new(
+                            "TypeError",
+                            "Pow function can only take numbers as arguments",
+                            0,
+                        ))))),
+                    },
                 },
             }),
         );
@@ -44,10 +94,21 @@ impl Interpreter {
             "min".to_string(),
             Value::NativeFunction(NativeFunction {
                 name: "min".to_string(),
-                arity: 2,
-                callable: |_, args| match (args[0].clone(), args[1].clone()) {
-                    (Value::Number(a), Value::Number(b)) => Ok(Value::Number(f64::min(a, b))),
-                    (_, _) => panic!("Min function can only take numbers as arguments"),
+                arity: 1..=usize::MAX,
+                callable: |_, args| {
+                    let mut least = args[0].clone();
+                    for arg in args {
+                        match compare(arg, &least) {
+                            Some(Ordering::Less) => least = arg.clone(),
+                            Some(_) => (),
+                            None => return Err(ErrorValue::new(
+                                "TypeError",
+                                format!("Min function can only take numbers, got {}", arg),
+                                0,
+                            )),
+                        }
+                    }
+                    Ok(least)
                 },
             }),
         );
@@ -55,10 +116,21 @@ impl Interpreter {
             "max".to_string(),
             Value::NativeFunction(NativeFunction {
                 name: "max".to_string(),
-                arity: 2,
-                callable: |_, args| match (args[0].clone(), args[1].clone()) {
-                    (Value::Number(a), Value::Number(b)) => Ok(Value::Number(f64::max(a, b))),
-                    (_, _) => panic!("Max function can only take numbers as arguments"),
+                arity: 1..=usize::MAX,
+                callable: |_, args| {
+                    let mut most = args[0].clone();
+                    for arg in args {
+                        match compare(arg, &most) {
+                            Some(Ordering::Greater) => most = arg.clone(),
+                            Some(_) => (),
+                            None => return Err(ErrorValue::new(
+                                "TypeError",
+                                format!("Max function can only take numbers, got {}", arg),
+                                0,
+                            )),
+                        }
+                    }
+                    Ok(most)
                 },
             }),
         );
@@ -66,10 +138,268 @@ impl Interpreter {
             "abs".to_string(),
             Value::NativeFunction(NativeFunction {
                 name: "abs".to_string(),
-                arity: 1,
+                arity: 1..=1,
+                callable: |interp, args| match (args[0].clone(), args[0].as_bigint()) {
+                    (_, Some(a)) => Ok(interp.ok(int_value(a.abs()))),
+                    (Value::Number(a), _) => Ok(interp.ok(Value::Number(a.abs()))),
+                    _ => Ok(interp.err(Value::Error(Rc::new(ErrorValue::new(
+                        "TypeError",
+                        "Abs function can only take a number as an argument",
+                        0,
+                    ))))),
+                },
+            }),
+        );
+        global_map.insert(
+            "range".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "range".to_string(),
+                arity: 2..=2,
+                callable: |_, args| match (args[0].clone(), args[1].clone()) {
+                    (Value::Int(start), Value::Int(end)) => Ok(Value::Range(Range::new(start, end))),
+                    (Value::BigInt(n), _) | (_, Value::BigInt(n)) => Err(range_too_big("bound", &n, 0)),
+                    (_, _) => Err(ErrorValue::new(
+                        "TypeError",
+                        "Range function can only take Ints as arguments",
+                        0,
+                    )),
+                },
+            }),
+        );
+        global_map.insert(
+            "divmod".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "divmod".to_string(),
+                arity: 2..=2,
+                callable: |_, args| match (args[0].as_bigint(), args[1].as_bigint()) {
+                    (Some(a), Some(b)) => match a.div_mod_floor(&b) {
+                        Some((q, r)) => Ok(Value::Tuple(Rc::new(vec![int_value(q), int_value(r)]))),
+                        None => Err(ErrorValue::new(
+                            "ZeroDivisionError",
+                            "Divmod function cant divide by zero",
+                            0,
+                        )),
+                    },
+                    _ => match (args[0].as_f64(), args[1].as_f64()) {
+                        (Some(_), Some(0.0)) => {
+                            Err(ErrorValue::new(
+                                "ZeroDivisionError",
+                                "Divmod function cant divide by zero",
+                                0,
+                            ))
+                        }
+                        (Some(a), Some(b)) => {
+                            let q = (a / b).floor();
+                            Ok(Value::Tuple(Rc::new(vec![
+                                Value::Number(q),
+                                Value::Number(a - b * q),
+                            ])))
+                        }
+                        _ => Err(ErrorValue::new(
+                            "TypeError",
+                            "Divmod function can only take numbers as arguments",
+                            0,
+                        )),
+                    },
+                },
+            }),
+        );
+        global_map.insert(
+            "len".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "len".to_string(),
+                arity: 1..=1,
+                callable: |_, args| match args[0].clone() {
+                    Value::List(l) => Ok(Value::Int(l.borrow().len() as i64)),
+                    Value::Tuple(t) => Ok(Value::Int(t.len() as i64)),
+                    Value::Dict(d) => Ok(Value::Int(d.borrow().len() as i64)),
+                    Value::String(s) => Ok(Value::Int(s.chars().count() as i64)),
+                    Value::Range(r) => Ok(match i64::try_from(r.len()) {
+                        Ok(n) => Value::Int(n),
+                        Err(_) => int_value(BigInt::parse(&r.len().to_string(), 10).unwrap()),
+                    }),
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Len function cant take {} as an argument", v),
+                        0,
+                    )),
+                },
+            }),
+        );
+        // int(3.9) is 3, floats are cut towards zero. int("42") // int(true)
+        global_map.insert(
+            "int".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "int".to_string(),
+                arity: 1..=1,
+                callable: |_, args| match args[0].clone() {
+                    n @ (Value::Int(_) | Value::BigInt(_)) => Ok(n),
+                    Value::Number(n) => match BigInt::from_f64(n.trunc()) {
+                        Some(n) => Ok(int_value(n)),
+                        None => Err(ErrorValue::new(
+                            "ValueError",
+                            format!("Cant turn {} into an Int", n),
+                            0,
+                        )),
+                    },
+                    Value::Bool(b) => Ok(Value::Int(b as i64)),
+                    Value::String(s) => match BigInt::parse(s.trim(), 10) {
+                        Some(n) => Ok(int_value(n)),
+                        None => Err(ErrorValue::new(
+                            "ValueError",
+                            format!("Cant turn \"{}\" into an Int", s),
+                            0,
+                        )),
+                    },
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Cant turn {} into an Int", v.type_name()),
+                        0,
+                    )),
+                },
+            }),
+        );
+        global_map.insert(
+            "float".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "float".to_string(),
+                arity: 1..=1,
+                callable: |_, args| match args[0].clone() {
+                    Value::Int(n) => Ok(Value::Number(n as f64)),
+                    Value::BigInt(n) => Ok(Value::Number(n.to_f64())),
+                    Value::Number(n) => Ok(Value::Number(n)),
+                    Value::Bool(b) => Ok(Value::Number(b as i64 as f64)),
+                    Value::String(s) => match s.trim().parse::<f64>() {
+                        Ok(n) => Ok(Value::Number(n)),
+                        Err(_) => Err(ErrorValue::new(
+                            "ValueError",
+                            format!("Cant turn \"{}\" into a float", s),
+                            0,
+                        )),
+                    },
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Cant turn {} into a float", v.type_name()),
+                        0,
+                    )),
+                },
+            }),
+        );
+        global_map.insert(
+            "push".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "push".to_string(),
+                arity: 2..=2,
+                callable: |_, args| match args[0].clone() {
+                    Value::List(l) => {
+                        l.borrow_mut().push(args[1].clone());
+                        Ok(Value::Nil)
+                    }
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Push function cant push onto {}", v),
+                        0,
+                    )),
+                },
+            }),
+        );
+        global_map.insert(
+            "pop".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "pop".to_string(),
+                arity: 1..=1,
+                callable: |_, args| match args[0].clone() {
+                    Value::List(l) => Ok(l.borrow_mut().pop().unwrap_or(Value::Nil)),
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Pop function cant pop from {}", v),
+                        0,
+                    )),
+                },
+            }),
+        );
+        global_map.insert(
+            "keys".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "keys".to_string(),
+                arity: 1..=1,
                 callable: |_, args| match args[0].clone() {
-                    Value::Number(a) => Ok(Value::Number(a.abs())),
-                    _ => panic!("Abs function can only take a number as an argument"),
+                    Value::Dict(d) => Ok(Value::List(Rc::new(RefCell::new(d.borrow().keys())))),
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Keys function cant take {} as an argument", v),
+                        0,
+                    )),
+                },
+            }),
+        );
+        global_map.insert(
+            "values".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "values".to_string(),
+                arity: 1..=1,
+                callable: |_, args| match args[0].clone() {
+                    Value::Dict(d) => Ok(Value::List(Rc::new(RefCell::new(d.borrow().values())))),
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Values function cant take {} as an argument", v),
+                        0,
+                    )),
+                },
+            }),
+        );
+        global_map.insert(
+            "has".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "has".to_string(),
+                arity: 2..=2,
+                callable: |_, args| match args[0].clone() {
+                    Value::Dict(d) => Ok(Value::Bool(d.borrow().contains(&args[1]))),
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Has function cant look for keys in {}", v),
+                        0,
+                    )),
+                },
+            }),
+        );
+        global_map.insert(
+            "remove".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "remove".to_string(),
+                arity: 2..=2,
+                callable: |_, args| match args[0].clone() {
+                    Value::Dict(d) => Ok(d.borrow_mut().remove(&args[1]).unwrap_or(Value::Nil)),
+                    v => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Remove function cant remove keys from {}", v),
+                        0,
+                    )),
+                },
+            }),
+        );
+        // error("message") // error("kind", "message") Throwing it fills in the line
+        global_map.insert(
+            "error".to_string(),
+            Value::NativeFunction(NativeFunction {
+                name: "error".to_string(),
+                arity: 1..=2,
+                callable: |_, args| match args {
+                    [message] => Ok(Value::Error(Rc::new(ErrorValue::new(
+                        "Error",
+                        message.to_string(),
+                        0,
+                    )))),
+                    [kind, message] => Ok(Value::Error(Rc::new(ErrorValue::new(
+                        &kind.to_string(),
+                        message.to_string(),
+                        0,
+                    )))),
+                    _ => Err(ErrorValue::new(
+                        "TypeError",
+                        "Error function takes a message and an optional kind",
+                        0,
+                    )),
                 },
             }),
         );
@@ -77,9 +407,9 @@ impl Interpreter {
             "print".to_string(),
             Value::NativeFunction(NativeFunction {
                 name: "print".to_string(),
-                arity: 1,
+                arity: 0..=usize::MAX,
                 callable: |_, args| {
-                    print!("{}", args[0].clone());
+                    print!("{}", join_args(args));
                     return Ok(Value::Nil);
                 },
             }),
@@ -88,9 +418,9 @@ impl Interpreter {
             "println".to_string(),
             Value::NativeFunction(NativeFunction {
                 name: "println".to_string(),
-                arity: 1,
+                arity: 0..=usize::MAX,
                 callable: |_, args| {
-                    println!("{}", args[0].clone());
+                    println!("{}", join_args(args));
                     return Ok(Value::Nil);
                 },
             }),
@@ -106,45 +436,112 @@ impl Interpreter {
             f_count: 0,
             global: global,
             lex_scope : lex_scope,
+            thrown: None,
+            result,
+            imports: HashMap::new(),
+            modules: HashMap::new(),
         }
     }
 
+    // Ok(v) and Err(e) are variants of the built in Result enum. Natives use these to report bad input
+    pub fn ok(&self, val: Value) -> Value {
+        Value::Variant(Rc::new(Variant {
+            def: self.result.clone(),
+            index: 0,
+            values: vec![val],
+        }))
+    }
+
+    pub fn err(&self, val: Value) -> Value {
+        Value::Variant(Rc::new(Variant {
+            def: self.result.clone(),
+            index: 1,
+            values: vec![val],
+        }))
+    }
+
     pub fn interp(&mut self, stmts: Vec<Statement>) {
         // self.program_scope = Scope::new(None);
         for s in stmts {
             match self.interp_statement(s) {
                 Ok(_) => (),
-                Err(e) => panic!("{}", e),
+                Err(e) => match self.thrown.take() {
+                    Some(val) => panic!("@Line {}: Uncaught {}", e.line, val),
+                    None => panic!("{}", e.report()),
+                },
             }
         }
     }
 
-    pub fn interp_statement(&mut self, stmt: Statement) -> Result<(), String> {
+    pub fn interp_statement(&mut self, stmt: Statement) -> Result<(), ErrorValue> {
         // println!("{}", stmt.);
         match stmt {
-            Statement::Declaration(sym, expr) => self.interp_declaration(sym, expr),
+            Statement::Declaration(pattern, _, expr) => self.interp_declaration(pattern, expr),
+            Statement::ConstDeclaration(pattern, _, expr) => {
+                let val = self.interp_expression(expr)?;
+                for (variable, v) in pattern.destructure(val).map_err(|e| e.at(pattern.line()))? {
+                    self.program_scope.define_const(variable, v);
+                }
+                Ok(())
+            }
             Statement::Expression(expr) => {
                 let _ = self.interp_expression(expr)?;
                 Ok(())
             }
-            Statement::Assignment(sym, expr) => self.interp_assignment(sym, expr),
-            Statement::FuncDclaration(name, params, body) => {
+            Statement::Assignment(sym, expr) => self.interp_assignment(sym, None, expr),
+            Statement::CompoundAssignment(sym, op, expr) => {
+                self.interp_assignment(sym, Some(op), expr)
+            }
+            Statement::AssignPattern(pattern, expr) => self.interp_assignpattern(pattern, expr),
+            Statement::FuncDclaration(name, params, _, body) => {
                 self.interp_funcdecl(name, params, body)
             }
-            Statement::Return(expr) => {
+            Statement::Return(_, expr) => {
                 self.return_val = Some(self.interp_expression(expr)?);
                 Ok(())
             }
-            Statement::StructDeclaration(sym) => {
-                let _ = self.interp_structdecl(sym)?;
+            Statement::Throw(keyword, expr) => {
+                let val = match self.interp_expression(expr)? {
+                    Value::Error(e) => Value::Error(Rc::new((*e).clone().at(keyword.line))),
+                    v => v,
+                };
+                let error = match &val {
+                    Value::Error(e) => (**e).clone(),
+                    v => ErrorValue::new("Error", v.to_string(), keyword.line),
+                };
+                self.thrown = Some(val);
+                Err(error)
+            }
+            Statement::StructDeclaration(sym, fields, methods) => {
+                self.interp_structdecl(sym, fields, methods)
+            }
+            Statement::EnumDeclaration(sym, variants) => {
+                let def = EnumDef {
+                    name: sym.name.clone(),
+                    variants: variants
+                        .into_iter()
+                        .map(|(v, fields)| (v.lex, fields.into_iter().map(|f| f.name).collect()))
+                        .collect(),
+                };
+                self.program_scope.define_var(sym, Value::Enum(Rc::new(def)));
                 Ok(())
             }
+            Statement::Import(path, sym, names) => self.interp_import(path, sym, names),
+            Statement::ImplDeclaration(sym, methods) => self.interp_impl(sym, methods),
+            Statement::ClassDeclaration(sym, superclass, methods) => {
+                self.interp_classdecl(sym, superclass, methods)
+            }
+            Statement::SetField(object, name, expr) => self.interp_setfield(object, name, expr),
+            Statement::SetIndex(object, bracket, index, expr) => {
+                self.interp_setindex(object, bracket, index, expr)
+            }
+            Statement::CompoundSet(target, op, expr) => self.interp_compound_set(target, op, expr),
             // Statement::Block(stmts) => self.interp_block(stmts),
             // Statement::While(condition, body) => self.interp_while(condition, body),
         }
     }
 
-    pub fn interp_expression(&mut self, expr: Expression) -> Result<Value, String> {
+    pub fn interp_expression(&mut self, expr: Expression) -> Result<Value, ErrorValue> {
         match expr {
             Expression::BreakExpr => {
                 return Ok(Value::Break);
@@ -152,81 +549,251 @@ impl Interpreter {
             Expression::ContinueExpr => {
                 return Ok(Value::Continue);
             }
-            Expression::Binary(l, operation, r) => self.interp_binary(l, operation, r),
-            Expression::Unary(operation, ex) => self.interp_unary(operation, ex),
+            Expression::Binary(l, operation, r) => self.interp_binary(*l, operation, *r),
+            Expression::Unary(operation, ex) => self.interp_unary(operation, *ex),
             Expression::Literal(a) => self.interp_literal(a),
             Expression::Grouping(ex) => self.interp_expression(*ex),
             Expression::Primary(v) => self.interp_variable(v),
-            Expression::Ternary(i, r0, r1) => self.interp_ternary(i, r0, r1),
+            Expression::Ternary(i, r0, r1) => self.interp_ternary(*i, *r0, *r1),
             Expression::Logical(r, o, l) => self.interp_logical(*r, o, *l),
-            Expression::Call(callee, t, args) => self.interp_call(callee, t, args),
+            Expression::Call(callee, t, args, named) => self.interp_call(*callee, t, args, named),
             Expression::BlockExpr(stmts) => self.interp_blockexpr(stmts),
-            Expression::IfExpr(conditon, then, elses) => self.inetrp_ifexpr(conditon, then, elses),
+            Expression::IfExpr(conditon, then, elses) => self.inetrp_ifexpr(*conditon, *then, *elses),
             Expression::WhileExpr(conditon, body) => self.interp_whileexpr(conditon, body),
             Expression::LoopExpr(body) => self.interp_loopexpr(body),
+            Expression::ForExpr(var, iterable, body) => self.interp_forexpr(var, *iterable, *body),
+            Expression::Get(object, name) => self.interp_get(*object, name),
+            Expression::StructInit(sym, fields) => self.interp_structinit(sym, fields),
+            Expression::This(sym) => self.look_up(sym),
+            Expression::List(items) => {
+                let mut values = vec![];
+                for item in items {
+                    values.push(self.interp_expression(item)?);
+                }
+                Ok(Value::List(Rc::new(RefCell::new(values))))
+            }
+            Expression::Match(keyword, subject, arms) => self.interp_match(keyword, *subject, arms),
+            Expression::Try(body, catch, finally) => self.interp_try(*body, catch, finally),
+            Expression::Propagate(question, expr) => self.interp_propagate(question, *expr),
+            Expression::Spread(_) => {
+                Err(ErrorValue::new("TypeError", "'..' can only spread the arguments of a call", 0))
+            }
+            Expression::Lambda(name, params, body) => {
+                Ok(Value::Function(self.new_function(name, params, *body, false)))
+            }
+            Expression::Interpolation(parts) => {
+                let mut s = String::new();
+                for part in parts {
+                    s.push_str(&self.interp_expression(part)?.to_string());
+                }
+                Ok(Value::String(s))
+            }
+            Expression::Tuple(items) => {
+                let mut values = vec![];
+                for item in items {
+                    values.push(self.interp_expression(item)?);
+                }
+                Ok(Value::Tuple(Rc::new(values)))
+            }
+            Expression::Dict(entries) => {
+                let mut dict = Dict::new();
+                for (key, val) in entries {
+                    let key = self.interp_expression(key)?;
+                    let val = self.interp_expression(val)?;
+                    dict.insert(key, val)?;
+                }
+                Ok(Value::Dict(Rc::new(RefCell::new(dict))))
+            }
+            Expression::Index(object, bracket, index) => self.interp_index(*object, bracket, *index),
+            Expression::Slice(object, bracket, start, end) => {
+                self.interp_slice(*object, bracket, start, end)
+            }
+            Expression::Super(sym, method) => self.interp_super(sym, method),
             // Expression::BreakExpr() => Ok(Value::Break),
             // Expression::Assignment(sym, expr) => Ok(self.interpret_assignment(sym, expr)),
             // _ => panic!("Error on interpreting expression. Unkown expression"),
         }
     }
 
-    pub fn interp_literal(&self, expr: Literal) -> Result<Value, String> {
+    pub fn interp_literal(&self, expr: Literal) -> Result<Value, ErrorValue> {
         match expr {
-            Literal::Str(s) => return Ok(Value::String(s)),
-            Literal::Number(n) => return Ok(Value::Number(n)),
-            Literal::True => return Ok(Value::Bool(true)),
-            Literal::False => return Ok(Value::Bool(false)),
-            Literal::Nil => return Ok(Value::Nil),
+            Literal::Str(s) => Ok(Value::String(s)),
+            Literal::Int(n) => Ok(Value::Int(n)),
+            Literal::BigInt(n) => Ok(Value::BigInt(Rc::new(n))),
+            Literal::Number(n) => Ok(Value::Number(n)),
+            Literal::True => Ok(Value::Bool(true)),
+            Literal::False => Ok(Value::Bool(false)),
+            Literal::Nil => Ok(Value::Nil),
         }
     }
 
-    fn interp_unary(&mut self, operation: Token, ex: Box<Expression>) -> Result<Value, String> {
-        let value = self
-            .interp_expression(*ex)
-            .expect("Unexpected Value on unary parsing");
+    fn interp_unary(&mut self, operation: Token, ex: Expression) -> Result<Value, ErrorValue> {
+        let value = self.interp_expression(ex)?;
         match (operation.token_type, value) {
             (TokenType::Minus, Value::Number(n)) => return Ok(Value::Number(-n)),
+            (TokenType::Minus, Value::Int(n)) => match n.checked_neg() {
+                Some(n) => Ok(Value::Int(n)),
+                None => Ok(Value::BigInt(Rc::new(-&BigInt::from(n)))),
+            },
+            (TokenType::Minus, Value::BigInt(n)) => Ok(int_value(-&*n)),
+            // ~x is -x - 1 for every Int
+            (TokenType::Tilde, Value::Int(n)) => Ok(Value::Int(!n)),
+            (TokenType::Tilde, Value::BigInt(n)) => Ok(int_value(&-&*n - &BigInt::from(1))),
+            (TokenType::Tilde, v) => Err(ErrorValue::new(
+                "TypeError",
+                format!("Cant use ~ on {}. Bitwise operators only work on ints", v.type_name()),
+                operation.line,
+            )),
             (TokenType::Not, Value::Bool(b)) => return Ok(Value::Bool(!b)),
-            _ => Err("Unexpected unary token. (Only ! and - accepted)".to_string()),
+            (_, v) => Err(ErrorValue::new(
+                "TypeError",
+                format!("Cant use {} on {}", operation.lex, v.type_name()),
+                operation.line,
+            )),
         }
     }
 
     fn interp_binary(
         &mut self,
-        l: Box<Expression>,
+        l: Expression,
         operation: Token,
-        r: Box<Expression>,
-    ) -> Result<Value, String> {
-        let left = self
-            .interp_expression(*l)
-            .expect("Error on binary interpret of left value");
-        let right = self
-            .interp_expression(*r)
-            .expect("Error on binary interpret of Right value");
+        r: Expression,
+    ) -> Result<Value, ErrorValue> {
+        let left = self.interp_expression(l)?;
+        let right = self.interp_expression(r)?;
+        self.binary_op(left, operation, right)
+    }
 
+    fn binary_op(&self, left: Value, operation: Token, right: Value) -> Result<Value, ErrorValue> {
         match (left, operation.token_type, right) {
+            (Value::Int(l), _, Value::Int(r)) if is_arithmetic(&operation.token_type) => {
+                int_op(l, &operation, r)
+            }
+            (Value::BigInt(l), _, Value::Int(r)) if is_arithmetic(&operation.token_type) => {
+                big_op((*l).clone(), &operation, BigInt::from(r))
+            }
+            (Value::Int(l), _, Value::BigInt(r)) if is_arithmetic(&operation.token_type) => {
+                big_op(BigInt::from(l), &operation, (*r).clone())
+            }
+            (Value::BigInt(l), _, Value::BigInt(r)) if is_arithmetic(&operation.token_type) => {
+                big_op((*l).clone(), &operation, (*r).clone())
+            }
+            (Value::Int(l), _, Value::Int(r)) if is_bitwise(&operation.token_type) => {
+                int_bitwise(l, &operation, r)
+            }
+            (l, _, r) if is_bitwise(&operation.token_type) => match (l.as_bigint(), r.as_bigint()) {
+                (Some(l), Some(r)) => big_bitwise(l, &operation, r),
+                _ => Err(ErrorValue::new(
+                    "TypeError",
+                    format!(
+                        "Cant use {} on {} and {}. Bitwise operators only work on ints",
+                        operation.lex,
+                        l.type_name(),
+                        r.type_name()
+                    ),
+                    operation.line,
+                )),
+            },
+            //Equality. Any two values can be compared
+            (l, TokenType::Equality, r) => Ok(Value::Bool(l == r)),
+            (l, TokenType::NotEqual, r) => Ok(Value::Bool(l != r)),
+            // 0..10 // 0..=10
+            (Value::Int(l), TokenType::DotDot, Value::Int(r)) => Ok(Value::Range(Range::new(l, r))),
+            (Value::Int(l), TokenType::DotDotEqual, Value::Int(r)) => {
+                Ok(Value::Range(Range::inclusive(l, r)))
+            }
+            (
+                Value::BigInt(n),
+                TokenType::DotDot | TokenType::DotDotEqual,
+                Value::Int(_) | Value::BigInt(_),
+            )
+            | (Value::Int(_), TokenType::DotDot | TokenType::DotDotEqual, Value::BigInt(n)) => {
+                Err(range_too_big("bound", &n, operation.line))
+            }
+            (l, TokenType::DotDot | TokenType::DotDotEqual, r) => Err(ErrorValue::new(
+                "TypeError",
+                format!("Range bounds must be ints, got {} and {}", l.type_name(), r.type_name()),
+                operation.line,
+            )),
+            (l, TokenType::In, r) => contains(&r, &l)
+                .map(Value::Bool)
+                .map_err(|e| e.at(operation.line)),
+            // Compared exactly. Turning the Int into a float first could round it onto the float
+            (
+                l @ (Value::Int(_) | Value::BigInt(_)),
+                TokenType::Less | TokenType::LessEqual | TokenType::Greater | TokenType::GreaterEqual,
+                r @ Value::Number(_),
+            )
+            | (
+                l @ Value::Number(_),
+                TokenType::Less | TokenType::LessEqual | TokenType::Greater | TokenType::GreaterEqual,
+                r @ (Value::Int(_) | Value::BigInt(_)),
+            ) => {
+                let order = compare(&l, &r);
+                Ok(Value::Bool(match operation.token_type {
+                    TokenType::Less => order == Some(Ordering::Less),
+                    TokenType::LessEqual => matches!(order, Some(Ordering::Less | Ordering::Equal)),
+                    TokenType::Greater => order == Some(Ordering::Greater),
+                    _ => matches!(order, Some(Ordering::Greater | Ordering::Equal)),
+                }))
+            }
+            // An Int used with a float is turned into a float
+            (Value::Int(l), _, r @ Value::Number(_)) => {
+                self.binary_op(Value::Number(l as f64), operation, r)
+            }
+            (l @ Value::Number(_), _, Value::Int(r)) => {
+                self.binary_op(l, operation, Value::Number(r as f64))
+            }
+            (Value::BigInt(l), _, r @ Value::Number(_)) => {
+                self.binary_op(Value::Number(l.to_f64()), operation, r)
+            }
+            (l @ Value::Number(_), _, Value::BigInt(r)) => {
+                self.binary_op(l, operation, Value::Number(r.to_f64()))
+            }
             //Numbers
             (Value::Number(l), TokenType::Plus, Value::Number(r)) => Ok(Value::Number(l + r)),
             (Value::Number(l), TokenType::Minus, Value::Number(r)) => Ok(Value::Number(l - r)),
             (Value::Number(l), TokenType::Aster, Value::Number(r)) => Ok(Value::Number(l * r)),
             (Value::Number(l), TokenType::Slash, Value::Number(r)) => match r {
-                r if r == 0.0 => panic!("Divide by zero!"),
+                r if r == 0.0 => Err(ErrorValue::new(
+                    "ZeroDivisionError",
+                    "Divide by zero",
+                    operation.line,
+                )),
                 _ => Ok(Value::Number(l / r)),
             },
+            // Floor division and modulo round towards negative infinity so -7 // 2 is -4 and -7 % 2 is 1
+            (Value::Number(l), TokenType::SlashSlash, Value::Number(r)) => {
+                if r == 0.0 {
+                    return Err(ErrorValue::new(
+                        "ZeroDivisionError",
+                        "Floor division by zero",
+                        operation.line,
+                    ));
+                }
+                Ok(Value::Number((l / r).floor()))
+            }
+            (Value::Number(l), TokenType::Percent, Value::Number(r)) => {
+                if r == 0.0 {
+                    return Err(ErrorValue::new(
+                        "ZeroDivisionError",
+                        "Modulo by zero",
+                        operation.line,
+                    ));
+                }
+                Ok(Value::Number(l - r * (l / r).floor()))
+            }
+            (Value::Number(l), TokenType::AsterAster, Value::Number(r)) => {
+                Ok(Value::Number(l.powf(r)))
+            }
             //Strings
             (Value::String(l), TokenType::Plus, Value::String(r)) => Ok(Value::String(l + &r)),
-            (Value::String(l), TokenType::Plus, Value::Number(r)) => {
+            (Value::String(l), TokenType::Plus, r @ (Value::Number(_) | Value::Int(_) | Value::BigInt(_))) => {
                 Ok(Value::String(l + &r.to_string()))
             }
-            (Value::Number(l), TokenType::Plus, Value::String(r)) => {
+            (l @ (Value::Number(_) | Value::Int(_) | Value::BigInt(_)), TokenType::Plus, Value::String(r)) => {
                 Ok(Value::String(l.to_string() + &r))
             }
-            //Logic
-            (Value::Bool(l), TokenType::Equality, Value::Bool(r)) => Ok(Value::Bool(l == r)),
-            (Value::Bool(l), TokenType::NotEqual, Value::Bool(r)) => Ok(Value::Bool(l != r)),
-            //Equality
-            (Value::Number(l), TokenType::Equality, Value::Number(r)) => Ok(Value::Bool(l == r)),
-            (Value::Number(l), TokenType::NotEqual, Value::Number(r)) => Ok(Value::Bool(l != r)),
             (Value::Number(l), TokenType::LessEqual, Value::Number(r)) => Ok(Value::Bool(l <= r)),
             (Value::Number(l), TokenType::GreaterEqual, Value::Number(r)) => {
                 Ok(Value::Bool(l >= r))
@@ -234,26 +801,30 @@ impl Interpreter {
             (Value::Number(l), TokenType::Less, Value::Number(r)) => Ok(Value::Bool(l < r)),
             (Value::Number(l), TokenType::Greater, Value::Number(r)) => Ok(Value::Bool(l > r)),
 
-            (_, _, _) => Err((operation.lex + " Binary expression error. Unkown operation.").to_string()),
+            (l, _, r) => Err(ErrorValue::new(
+                "TypeError",
+                format!("Cant use {} on {} and {}", operation.lex, l.type_name(), r.type_name()),
+                operation.line,
+            )),
         }
     }
 
     fn interp_declaration(
         &mut self,
-        variable: Symbol,
+        pattern: Pattern,
         expr: Option<Expression>,
-    ) -> Result<(), String> {
-        match expr {
-            Some(expr) => match self.interp_expression(expr) {
-                Ok(v) => self.program_scope.define_var(variable, v),
-                Err(e) => return Err(e),
-            },
-            None => self.program_scope.define_var(variable, Value::Nil),
+    ) -> Result<(), ErrorValue> {
+        let val = match expr {
+            Some(expr) => self.interp_expression(expr)?,
+            None => Value::Nil,
+        };
+        for (variable, v) in pattern.destructure(val).map_err(|e| e.at(pattern.line()))? {
+            self.program_scope.define_var(variable, v);
         }
         Ok(())
     }
 
-    fn interp_variable(&self, v: Symbol) -> Result<Value, String> {
+    fn interp_variable(&self, v: Symbol) -> Result<Value, ErrorValue> {
      // match self.program_scope.get_var(v.clone()) {
         //     Ok(v) => Ok(v.clone()),
         //     Err(err) => match self.global.get_var(v.clone()) {
@@ -266,38 +837,64 @@ impl Interpreter {
     // Remove the return result to go back to normal assignment
     // Assignment is currently an expression meaning something like print x = 2; will print 2 and all assign variable x to 2;
     // When assignment is a statment it would throw an error for print x = 2; and assignment would always look like y = 3;
-    fn interp_assignment(&mut self, sym: Symbol, expr: Expression) -> Result<(), String> {
-        let distance = self.lex_scope.get(&sym.s_id).expect(&format!("{} not found in lex scope.", sym.name)).clone();
-        match self.interp_expression(expr) {
-            Ok(v) => {
-                // self.program_scope.assign_var(&sym, v);
-                self.program_scope.assign_at(sym, v, distance);
-            }
-            Err(e) => return Err(e),
+    // x += 1 reads x once, applies the operator and stores the result back at the same depth
+    fn interp_assignment(
+        &mut self,
+        sym: Symbol,
+        op: Option<Token>,
+        expr: Expression,
+    ) -> Result<(), ErrorValue> {
+        let mut val = self.interp_expression(expr)?;
+        if let Some(op) = op {
+            let current = self.look_up(sym.clone())?;
+            val = self.binary_op(current, op, val)?;
+        }
+        self.assign(sym, val)
+    }
+
+    fn interp_assignpattern(&mut self, pattern: Pattern, expr: Expression) -> Result<(), ErrorValue> {
+        let val = self.interp_expression(expr)?;
+        for (sym, v) in pattern.destructure(val).map_err(|e| e.at(pattern.line()))? {
+            self.assign(sym, v)?;
         }
         Ok(())
     }
 
-    // fn interpreter_error(&self, tok: Token, msg: &str) -> Result<Value, String> {
+    // Like look_up, a name the resolver couldn't find can only be a global
+    fn assign(&mut self, sym: Symbol, val: Value) -> Result<(), ErrorValue> {
+        match self.lex_scope.get(&sym.s_id) {
+            Some(distance) => self.program_scope.assign_at(sym, val, *distance),
+            None if self.global.contains_key(&sym) => self.global.assign_at(sym, val, 0),
+            None => Err(ErrorValue::new(
+                "NameError",
+                format!("{} is not defined", sym.name),
+                sym.line,
+            )),
+        }
+    }
+
+    // fn interpreter_error(&self, tok: Token, msg: &str) -> Result<Value, ErrorValue> {
     //     Err(format!("Intepreter Error @ {}: {}", tok.line, msg))
     // }
 
     fn interp_ternary(
         &mut self,
-        i: Box<Expression>,
-        r0: Box<Expression>,
-        r1: Box<Expression>,
-    ) -> Result<Value, String> {
-        let interp_i = self.interp_expression(*i);
+        i: Expression,
+        r0: Expression,
+        r1: Expression,
+    ) -> Result<Value, ErrorValue> {
+        let interp_i = self.interp_expression(i);
         match interp_i {
             Ok(v) => match v {
                 Value::Bool(b) => match b {
-                    true => return self.interp_expression(*r0),
-                    false => return self.interp_expression(*r1),
+                    true => return self.interp_expression(r0),
+                    false => return self.interp_expression(r1),
                 },
-                _ => Err(
-                    "First expression of Ternary expression must be boolean expression".to_string(),
-                ),
+                _ => Err(ErrorValue::new(
+                    "TypeError",
+                    "First expression of Ternary expression must be boolean expression",
+                    0,
+                )),
             },
             Err(e) => Err(e),
         }
@@ -308,94 +905,550 @@ impl Interpreter {
         left_expr: Expression,
         o: Token,
         right_expr: Expression,
-    ) -> Result<Value, String> {
+    ) -> Result<Value, ErrorValue> {
         match o.token_type {
             TokenType::And => {
-                match self
-                    .interp_expression(left_expr)
-                    .expect("Error in logical statement")
-                {
+                match self.interp_expression(left_expr)? {
                     Value::Bool(v) => match v {
-                        true => {
-                            match self
-                                .interp_expression(right_expr)
-                                .expect("Error in logical statement")
-                            {
-                                Value::Bool(v) => match v {
-                                    true => return Ok(Value::Bool(true)),
-                                    false => return Ok(Value::Bool(false)),
-                                },
-                                _ => panic!("Error logical expressions should amount to bool"),
-                            }
-                        }
+                        true => match self.interp_expression(right_expr)? {
+                            Value::Bool(v) => match v {
+                                true => return Ok(Value::Bool(true)),
+                                false => return Ok(Value::Bool(false)),
+                            },
+                            _ => Err(ErrorValue::new(
+                                "TypeError",
+                                "Logical expressions should amount to bool",
+                                o.line,
+                            )),
+                        },
                         false => return Ok(Value::Bool(false)),
                     },
-                    _ => panic!("Error logical expressions should amount to bool"),
+                    _ => Err(ErrorValue::new(
+                        "TypeError",
+                        "Logical expressions should amount to bool",
+                        o.line,
+                    )),
                 }
             }
             TokenType::Or => {
-                let left = self
-                    .interp_expression(left_expr)
-                    .expect("Error in logical statement.");
-                let right = self
-                    .interp_expression(right_expr)
-                    .expect("Error in logical statement.");
+                let left = self.interp_expression(left_expr)?;
+                let right = self.interp_expression(right_expr)?;
 
                 match (left, right) {
                     (Value::Bool(l), Value::Bool(r)) => Ok(Value::Bool(l || r)),
-                    _ => Err("Error logical expressions should amount to bool".to_string()),
+                    _ => Err(ErrorValue::new(
+                        "TypeError",
+                        "Logical expressions should amount to bool",
+                        o.line,
+                    )),
                 }
             }
-            _ => Err("Logical operators are 'and', 'or'".to_string()), // Shouldn't be possible
+            _ => Err(ErrorValue::new(
+                "TypeError",
+                "Logical operators are 'and', 'or'",
+                0,
+            )), // Shouldn't be possible
         }
     }
 
     fn interp_funcdecl(
         &mut self,
         name: Symbol,
-        params: Vec<Symbol>,
+        params: Vec<Param>,
+        body: Expression,
+    ) -> Result<(), ErrorValue> {
+        let f_id = self.new_function(name.clone(), params, body, false);
+        self.program_scope.define_var(name, Value::Function(f_id));
+        Ok(())
+    }
+
+    // Adds a function closing over the current scope to the function map and returns its id
+    fn new_function(
+        &mut self,
+        name: Symbol,
+        params: Vec<Param>,
         body: Expression,
-    ) -> Result<(), String> {
+        method: bool,
+    ) -> u64 {
         let stmts ;
         match body {
             Expression::BlockExpr(a) => stmts = a,
             _ => panic!("Funciton body must be a block. surrounded by {{ }}"),
         }
-        let func = Function::new(name.clone(), params,
+        let mut func = Function::new(name, params,
             stmts,
             self.program_scope.clone(),
             self.f_count,
         );
-        let func_value = Value::Function(self.f_count);
+        func.method = method;
         self.function_map.insert(self.f_count, func);
         self.f_count = self.f_count + 1;
-        self.program_scope.define_var(name, func_value);
-        Ok(())
+        self.f_count - 1
     }
 
     fn interp_structdecl(
         &mut self,
-        name : Symbol,
-    ) -> Result<(),String>{
-        todo!("Need to do structs");
+        name: Symbol,
+        fields: Vec<(Symbol, Option<Token>)>,
+        methods: Vec<Statement>,
+    ) -> Result<(), ErrorValue> {
+        let def = StructDef::new(
+            name.name.clone(),
+            fields.into_iter().map(|(f, _)| f.name).collect(),
+        );
+        self.add_methods(&def, methods);
+        self.program_scope
+            .define_var(name, Value::StructDef(Rc::new(def)));
+        Ok(())
+    }
+
+    fn interp_index(
+        &mut self,
+        object: Expression,
+        bracket: Token,
+        index: Expression,
+    ) -> Result<Value, ErrorValue> {
+        let object = self.interp_expression(object)?;
+        let index = self.interp_expression(index)?;
+        self.index(object, &bracket, index)
+    }
+
+    fn index(&self, object: Value, bracket: &Token, index: Value) -> Result<Value, ErrorValue> {
+        // xs[1..3] is the same as xs[1:3]
+        if let Value::Range(r) = index {
+            return slice_range(object, r, bracket.line);
+        }
+        match object {
+            Value::List(l) => {
+                let l = l.borrow();
+                Ok(l[list::index_of(&index, l.len(), bracket.line)?].clone())
+            }
+            Value::Tuple(t) => Ok(t[list::index_of(&index, t.len(), bracket.line)?].clone()),
+            Value::String(s) => {
+                let chars: Vec<char> = s.chars().collect();
+                let i = list::index_of(&index, chars.len(), bracket.line)?;
+                Ok(Value::String(chars[i].to_string()))
+            }
+            Value::Dict(d) => match d.borrow().get(&index) {
+                Some(v) => Ok(v),
+                None => Err(ErrorValue::new(
+                    "KeyError",
+                    format!("Key {} not found", index),
+                    bracket.line,
+                )),
+            },
+            v => Err(ErrorValue::new("TypeError", format!("Cant index into {}", v), bracket.line)),
+        }
+    }
+
+    // Slicing always makes a new list
+    fn interp_slice(
+        &mut self,
+        object: Expression,
+        bracket: Token,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>,
+    ) -> Result<Value, ErrorValue> {
+        let object = self.interp_expression(object)?;
+        let start = match start {
+            Some(s) => Some(self.interp_expression(*s)?),
+            None => None,
+        };
+        let end = match end {
+            Some(e) => Some(self.interp_expression(*e)?),
+            None => None,
+        };
+        slice(object, start, end, 1, bracket.line)
+    }
+
+    fn interp_setindex(
+        &mut self,
+        object: Expression,
+        bracket: Token,
+        index: Expression,
+        expr: Expression,
+    ) -> Result<(), ErrorValue> {
+        let object = self.interp_expression(object)?;
+        let index = self.interp_expression(index)?;
+        let val = self.interp_expression(expr)?;
+        self.set_index(object, &bracket, index, val)
+    }
+
+    fn set_index(&self, object: Value, bracket: &Token, index: Value, val: Value) -> Result<(), ErrorValue> {
+        match object {
+            Value::List(l) => {
+                let mut l = l.borrow_mut();
+                let i = list::index_of(&index, l.len(), bracket.line)?;
+                l[i] = val;
+                Ok(())
+            }
+            Value::Dict(d) => d
+                .borrow_mut()
+                .insert(index, val)
+                .map_err(|e| e.at(bracket.line)),
+            v => Err(ErrorValue::new(
+                "TypeError",
+                format!("Cant assign to an index of {}", v),
+                bracket.line,
+            )),
+        }
+    }
+
+    // xs[i] += 1 reads and writes the same list and index
+    fn interp_compound_set(&mut self, target: Expression, op: Token, expr: Expression) -> Result<(), ErrorValue> {
+        match target {
+            Expression::Get(object, name) => {
+                let object = self.interp_expression(*object)?;
+                let current = self.get(object.clone(), &name)?;
+                let right = self.interp_expression(expr)?;
+                let val = self.binary_op(current, op, right)?;
+                self.set_field(object, &name, val)
+            }
+            Expression::Index(object, bracket, index) => {
+                let object = self.interp_expression(*object)?;
+                let index = self.interp_expression(*index)?;
+                let current = self.index(object.clone(), &bracket, index.clone())?;
+                let right = self.interp_expression(expr)?;
+                let val = self.binary_op(current, op, right)?;
+                self.set_index(object, &bracket, index, val)
+            }
+            _ => unreachable!("The parser only makes compound assignments to fields and indexes"),
+        }
+    }
+
+    fn interp_classdecl(
+        &mut self,
+        name: Symbol,
+        superclass: Option<Symbol>,
+        methods: Vec<Statement>,
+    ) -> Result<(), ErrorValue> {
+        let sup = match superclass {
+            Some(sym) => match self.look_up(sym.clone())? {
+                Value::StructDef(def) if def.is_class => Some(def),
+                _ => return Err(ErrorValue::new(
+                    "TypeError",
+                    format!("Superclass {} must be a class", sym.name),
+                    0,
+                )),
+            },
+            None => None,
+        };
+        let def = StructDef::class(name.name.clone(), sup.clone());
+        // Methods of a subclass close over a scope that holds `super`
+        if let Some(sup) = sup {
+            let enclosing = self.program_scope.clone();
+            self.program_scope = Scope::new(Some(Box::new(enclosing.clone())));
+            self.program_scope.define_var(
+                Symbol {
+                    name: "super".to_string(),
+                    s_id: 0,
+                    line: 0,
+                },
+                Value::StructDef(sup),
+            );
+            self.add_methods(&def, methods);
+            self.program_scope = enclosing;
+        } else {
+            self.add_methods(&def, methods);
+        }
+        self.program_scope
+            .define_var(name, Value::StructDef(Rc::new(def)));
         Ok(())
     }
 
+    // `this` is always one scope closer than `super`
+    fn interp_super(&mut self, sym: Symbol, method: Token) -> Result<Value, ErrorValue> {
+        let distance = *self
+            .lex_scope
+            .get(&sym.s_id)
+            .expect("super not found in lex scope.");
+        let superclass = match self.program_scope.get_at(sym, distance)? {
+            Value::StructDef(def) => def,
+            _ => return Err(ErrorValue::new("TypeError", "super must be a class", 0)),
+        };
+        let this = self.program_scope.get_at(
+            Symbol {
+                name: "this".to_string(),
+                s_id: 0,
+                line: 0,
+            },
+            distance - 1,
+        )?;
+        self.bind_method(this, &superclass, &method)
+    }
+
+    // Calling a class makes a new instance and runs its init method with the arguments
+    fn instantiate(
+        &mut self,
+        def: Rc<StructDef>,
+        args: Vec<Value>,
+        named: Vec<(Token, Value)>,
+        line: usize,
+    ) -> Result<Value, ErrorValue> {
+        let instance = Value::Struct(StructInstance::new(def.clone()).shared());
+        match def.find_method("init") {
+            Some(f_id) => {
+                let name = format!("{}.init", def.name);
+                let mut init = BoundMethod::new(instance.clone(), name, &self.function_map[&f_id]);
+                let args = arrange_args(&init, args, named)
+                    .map_err(|e| e.at(line))?;
+                init.call(self, &args)?;
+                self.return_val = None;
+            }
+            None => {
+                if !args.is_empty() || !named.is_empty() {
+                    return Err(ErrorValue::new(
+                        "TypeError",
+                        format!("{} has no init method and takes no arguments", def.name),
+                        line,
+                    ));
+                }
+            }
+        }
+        Ok(instance)
+    }
+
+    fn interp_import(
+        &mut self,
+        path: Token,
+        sym: Symbol,
+        names: Option<Vec<Symbol>>,
+    ) -> Result<(), ErrorValue> {
+        let module = match self.imports.get(&sym.s_id) {
+            Some(module) => module.clone(),
+            None => {
+                return Err(ErrorValue::new(
+                    "ImportError",
+                    "Imports only work when running a file",
+                    path.line,
+                ))
+            }
+        };
+        let loaded = self.load_module(&module).map_err(|e| e.at(path.line))?;
+        match names {
+            Some(names) => {
+                for name in names {
+                    let val = loaded
+                        .get(&name.name)
+                        .map_err(|e| e.at(path.line))?;
+                    // Names declared with const in the module are const here too
+                    if loaded.constants.borrow().contains(&name.name) {
+                        self.program_scope.define_const(name, val);
+                    } else {
+                        self.program_scope.define_var(name, val);
+                    }
+                }
+            }
+            None => self.program_scope.define_var(sym, Value::Module(loaded)),
+        }
+        Ok(())
+    }
+
+    // A module runs the first time it is imported in a scope of its own. Later imports share what it made
+    fn load_module(&mut self, module: &Module) -> Result<Rc<LoadedModule>, ErrorValue> {
+        if let Some(loaded) = self.modules.get(&module.path) {
+            return Ok(loaded.clone());
+        }
+        let old_scope = std::mem::replace(&mut self.program_scope, Scope::new(None));
+        for stmt in module.stmts.clone() {
+            if let Err(e) = self.interp_statement(stmt) {
+                self.program_scope = old_scope;
+                // Reported at the import, so what was thrown is wrapped up with where it came from
+                self.thrown = None;
+                let message = format!("In {} on line {}: {}", module.path.display(), e.line, e.message);
+                return Err(ErrorValue::new(&e.kind, message, 0));
+            }
+        }
+        let scope = std::mem::replace(&mut self.program_scope, old_scope);
+        let loaded = Rc::new(LoadedModule {
+            name: module.name.clone(),
+            values: scope.values,
+            constants: scope.constants,
+        });
+        self.modules.insert(module.path.clone(), loaded.clone());
+        Ok(loaded)
+    }
+
+    fn interp_impl(&mut self, name: Symbol, methods: Vec<Statement>) -> Result<(), ErrorValue> {
+        match self.look_up(name.clone())? {
+            Value::StructDef(def) => {
+                self.add_methods(&def, methods);
+                Ok(())
+            }
+            _ => Err(ErrorValue::new(
+                "TypeError",
+                format!("Cant impl {}. It is not a struct", name.name),
+                0,
+            )),
+        }
+    }
+
+    fn add_methods(&mut self, def: &StructDef, methods: Vec<Statement>) {
+        for method in methods {
+            if let Statement::FuncDclaration(name, params, _, body) = method {
+                let method_name = name.name.clone();
+                let f_id = self.new_function(name, params, body, true);
+                def.methods.borrow_mut().insert(method_name, f_id);
+            }
+        }
+    }
+
+    // Fields are looked up before methods. Methods found through an instance have it bound to `this`
+    fn bind_method(&self, receiver: Value, def: &StructDef, name: &Token) -> Result<Value, ErrorValue> {
+        match def.find_method(&name.lex) {
+            Some(f_id) => Ok(Value::BoundMethod(Rc::new(BoundMethod::new(
+                receiver,
+                name.lex.clone(),
+                &self.function_map[&f_id],
+            )))),
+            None => Err(ErrorValue::new(
+                "AttributeError",
+                format!("Struct {} has no field or method {}", def.name, name.lex),
+                name.line,
+            )),
+        }
+    }
+
+    fn interp_structinit(
+        &mut self,
+        name: Symbol,
+        fields: Vec<(Token, Expression)>,
+    ) -> Result<Value, ErrorValue> {
+        let def = match self.look_up(name.clone())? {
+            Value::StructDef(def) => def,
+            _ => return Err(ErrorValue::new(
+                "TypeError",
+                format!("{} is not a struct", name.name),
+                0,
+            )),
+        };
+        let mut instance = StructInstance::new(def);
+        for (field, expr) in fields {
+            let val = self.interp_expression(expr)?;
+            instance
+                .set(&field.lex, val)
+                .map_err(|e| e.at(field.line))?;
+        }
+        Ok(Value::Struct(instance.shared()))
+    }
+
+    fn interp_get(&mut self, object: Expression, name: Token) -> Result<Value, ErrorValue> {
+        let object = self.interp_expression(object)?;
+        self.get(object, &name)
+    }
+
+    fn get(&self, object: Value, name: &Token) -> Result<Value, ErrorValue> {
+        match object {
+            Value::Struct(instance) => {
+                let (field, def) = {
+                    let i = instance.borrow();
+                    (i.get(&name.lex), i.def.clone())
+                };
+                match field {
+                    Ok(v) => Ok(v),
+                    Err(_) => self.bind_method(Value::Struct(instance), &def, name),
+                }
+            }
+            Value::StructDef(def) => match def.find_method(&name.lex) {
+                Some(f_id) => Ok(Value::Function(f_id)),
+                None => Err(ErrorValue::new(
+                    "AttributeError",
+                    format!("Struct {} has no associated function {}", def.name, name.lex),
+                    name.line,
+                )),
+            },
+            // Shape.Empty is a variant already, Shape.Circle has to be called with its values
+            Value::Enum(def) => match def.variant(&name.lex) {
+                Some(index) if def.variants[index].1.is_empty() => {
+                    Ok(Value::Variant(Rc::new(Variant { def, index, values: vec![] })))
+                }
+                Some(index) => Ok(Value::VariantConstructor(Rc::new(VariantConstructor {
+                    def,
+                    index,
+                }))),
+                None => Err(ErrorValue::new(
+                    "AttributeError",
+                    format!("Enum {} has no variant {}", def.name, name.lex),
+                    name.line,
+                )),
+            },
+            Value::Variant(v) => v.get(&name.lex).map_err(|e| e.at(name.line)),
+            Value::Error(e) => e.get(&name.lex).map_err(|e| e.at(name.line)),
+            Value::Module(m) => m.get(&name.lex).map_err(|e| e.at(name.line)),
+            // (0..10).step(2)
+            Value::Range(r) if name.lex == "step" => Ok(Value::NativeMethod(Rc::new(NativeMethod {
+                receiver: Value::Range(r),
+                native: NativeFunction {
+                    name: "step".to_string(),
+                    arity: 1..=1, // Not counting the range itself
+                    callable: |_, args| match (&args[0], &args[1]) {
+                        (Value::Range(r), Value::Int(step)) => r.step(*step).map(Value::Range),
+                        (_, Value::BigInt(n)) => Err(range_too_big("step", n, 0)),
+                        (_, v) => Err(ErrorValue::new(
+                            "TypeError",
+                            format!("Range step must be an int, got {}", v),
+                            0,
+                        )),
+                    },
+                },
+            }))),
+            v => Err(ErrorValue::new(
+                "AttributeError",
+                format!("Cant get field {} of {}. Only structs have fields", name.lex, v),
+                name.line,
+            )),
+        }
+    }
+
+    fn interp_setfield(
+        &mut self,
+        object: Expression,
+        name: Token,
+        expr: Expression,
+    ) -> Result<(), ErrorValue> {
+        let object = self.interp_expression(object)?;
+        let val = self.interp_expression(expr)?;
+        self.set_field(object, &name, val)
+    }
+
+    fn set_field(&self, object: Value, name: &Token, val: Value) -> Result<(), ErrorValue> {
+        match object {
+            Value::Struct(instance) => instance
+                .borrow_mut()
+                .set(&name.lex, val)
+                .map_err(|e| e.at(name.line)),
+            v => Err(ErrorValue::new(
+                "AttributeError",
+                format!("Cant set field {} of {}. Only structs have fields", name.lex, v),
+                name.line,
+            )),
+        }
+    }
+
     fn interp_call(
         &mut self,
-        callee: Box<Expression>,
+        callee: Expression,
         t: Token,
         args: Vec<Expression>,
-    ) -> Result<Value, String> {
+        named: Vec<(Token, Expression)>,
+    ) -> Result<Value, ErrorValue> {
         // let callee = self.interp_expression(*callee).expect("Error on callee");
-        let arguments: Vec<Value> = args
-            .iter()
-            .map(|a| {
-                self.interp_expression(a.clone())
-                    .expect("Error interpreting call arguments")
-            })
-            .collect();
-        self.call(callee, t, arguments)
+        let mut arguments: Vec<Value> = vec![];
+        for a in args {
+            match a {
+                // f(..xs) passes each item of xs as its own argument
+                Expression::Spread(items) => {
+                    let items = self.interp_expression(*items)?;
+                    arguments.extend(self.iterate(items).map_err(|e| e.at(t.line))?);
+                }
+                a => arguments.push(self.interp_expression(a)?),
+            }
+        }
+        let mut named_arguments: Vec<(Token, Value)> = vec![];
+        for (name, a) in named {
+            named_arguments.push((name, self.interp_expression(a)?));
+        }
+        self.call(callee, t, arguments, named_arguments)
         // let func =
         // self.program_scope.get_func();
         // return func.call()
@@ -403,22 +1456,34 @@ impl Interpreter {
 
     fn call(
         &mut self,
-        callee_expr: Box<Expression>,
+        callee_expr: Expression,
         loc: Token,
         args: Vec<Value>,
-    ) -> Result<Value, String> {
-        let callee = self.interp_expression(*callee_expr)?;
-        let fval;
-        match match_callable(self, callee) {
-            Some(mut f) => {
-                if args.len() != f.arity() {
-                    panic!("Argument length exceeds paramter length");
-                } else {
-                    fval = f.call(self, &args)?;
-                }
+        named: Vec<(Token, Value)>,
+    ) -> Result<Value, ErrorValue> {
+        let callee = self.interp_expression(callee_expr)?;
+        if let Value::StructDef(def) = callee {
+            if def.is_class {
+                return self.instantiate(def, args, named, loc.line);
             }
-            None => todo!(),
+            return Err(ErrorValue::new(
+                "TypeError",
+                format!(
+                    "Struct {} cant be called. Use {} {{ ... }} to make one",
+                    def.name, def.name
+                ),
+                loc.line,
+            ));
         }
+        let fval = match match_callable(self, callee) {
+            Ok(mut f) => {
+                let args = arrange_args(f.as_ref(), args, named)
+                    .map_err(|e| e.at(loc.line))?;
+                // Natives don't know where they were called from
+                f.call(self, &args).map_err(|e| e.at(loc.line))?
+            }
+            Err(e) => return Err(e.at(loc.line)),
+        };
         let return_val = self.return_val.clone();
         self.return_val = None;
         match return_val {
@@ -429,7 +1494,7 @@ impl Interpreter {
         //Create new scope
     }
 
-    fn interp_blockexpr(&mut self, stmts: Vec<Statement>) -> Result<Value, String> {
+    fn interp_blockexpr(&mut self, stmts: Vec<Statement>) -> Result<Value, ErrorValue> {
         let old_scope = self.program_scope.clone();
         self.program_scope = Scope::new(Some(Box::new(old_scope)));
         let mut last: Value = Value::Nil;
@@ -466,6 +1531,10 @@ impl Interpreter {
                     last = Value::Nil
                 }
             }
+            // A return inside the block skips the rest of it
+            if self.return_val.is_some() {
+                break;
+            }
         }
         //End block and revert to previous scope
         if let Some(scope) = &self.program_scope.enclosing {
@@ -480,53 +1549,161 @@ impl Interpreter {
 
     fn inetrp_ifexpr(
         &mut self,
-        conditon: Box<Expression>,
-        then: Box<Expression>,
-        elses: Box<Option<Expression>>,
-    ) -> Result<Value, String> {
-        match self
-            .interp_expression(*conditon)
-            .expect("Error interpreting if statement")
-        {
+        conditon: Expression,
+        then: Expression,
+        elses: Option<Expression>,
+    ) -> Result<Value, ErrorValue> {
+        match self.interp_expression(conditon)? {
             Value::Bool(b) => match b {
                 true => {
-                    let r = self.interp_expression(*then)?;
+                    let r = self.interp_expression(then)?;
                     return Ok(r);
                 }
-                false => match *elses {
+                false => match elses {
                     Some(expr) => {self.interp_expression(expr)},
                     None => return Ok(Value::Nil),
                 },
             },
-            _ => return Err("Condition of if statement does not amount to boolean".to_string()), //Error expression does not amount to true false value
+            v => {
+                return Err(ErrorValue::new(
+                    "TypeError",
+                    format!("Condition of if statement does not amount to boolean, got {}", v),
+                    0,
+                ))
+            } //Error expression does not amount to true false value
+        }
+    }
+
+    /*The first arm whose pattern fits the value and whose guard is true is evaluated in a new scope
+    holding the names its pattern binds. No arm matching is an error */
+    fn interp_match(
+        &mut self,
+        keyword: Token,
+        subject: Expression,
+        arms: Vec<MatchArm>,
+    ) -> Result<Value, ErrorValue> {
+        let val = self.interp_expression(subject)?;
+        for arm in arms {
+            let bound = match arm.pattern.destructure(val.clone()) {
+                Ok(bound) => bound,
+                Err(_) => continue,
+            };
+            let old_scope = self.program_scope.clone();
+            self.program_scope = Scope::new(Some(Box::new(old_scope.clone())));
+            for (sym, v) in bound {
+                self.program_scope.define_var(sym, v);
+            }
+            let matched = match arm.guard {
+                Some(guard) => match self.interp_expression(guard) {
+                    Ok(Value::Bool(b)) => Ok(b),
+                    Ok(v) => Err(ErrorValue::new(
+                        "TypeError",
+                        format!("Match guard must be a boolean, got {}", v),
+                        keyword.line,
+                    )),
+                    Err(e) => Err(e),
+                },
+                None => Ok(true),
+            };
+            let result = match matched {
+                Ok(true) => Some(self.interp_expression(arm.body)),
+                Ok(false) => None,
+                Err(e) => Some(Err(e)),
+            };
+            self.program_scope = old_scope;
+            if let Some(result) = result {
+                return result;
+            }
+        }
+        Err(ErrorValue::new("MatchError", format!("No match arm matched {}", val), keyword.line))
+    }
+
+    /*Evaluates to the try block, or to the catch block when the try block fails. The finally block always runs
+    and its value is thrown away. Errors unwind without putting the scope back so it is restored here */
+    fn interp_try(
+        &mut self,
+        body: Expression,
+        catch: Option<Box<Catch>>,
+        finally: Option<Box<Expression>>,
+    ) -> Result<Value, ErrorValue> {
+        let old_scope = self.program_scope.clone();
+        let mut result = self.interp_expression(body);
+        // A ? returning from the function isn't an error
+        let returning = self.return_val.is_some();
+        if let (Err(e), Some(catch), false) = (&result, catch, returning) {
+            let error = match self.thrown.take() {
+                Some(v) => v,
+                None => Value::Error(Rc::new(e.clone())),
+            };
+            self.program_scope = Scope::new(Some(Box::new(old_scope.clone())));
+            if let Some(name) = catch.name {
+                self.program_scope.define_var(name, error);
+            }
+            result = self.interp_expression(catch.body);
+        }
+        self.program_scope = old_scope;
+        if let Some(finally) = finally {
+            // A return or throw waiting on the finally block picks up again once it is done
+            let thrown = self.thrown.take();
+            let return_val = self.return_val.take();
+            self.interp_expression(*finally)?;
+            if self.return_val.is_none() {
+                self.return_val = return_val;
+            }
+            self.thrown = thrown;
+        }
+        result
+    }
+
+    /*Ok(v)? is v. Err(e)? returns Err(e) from the function straight away. The return is carried out
+    by an Err with return_val set, Function::invoke turns it back into a normal return */
+    fn interp_propagate(&mut self, question: Token, expr: Expression) -> Result<Value, ErrorValue> {
+        match self.interp_expression(expr)? {
+            Value::Variant(v) if Rc::ptr_eq(&v.def, &self.result) => match v.index {
+                0 => Ok(v.values[0].clone()),
+                _ => {
+                    self.return_val = Some(Value::Variant(v));
+                    Err(ErrorValue::new("Error", "? returned an Err", question.line))
+                }
+            },
+            v => Err(ErrorValue::new(
+                "TypeError",
+                format!("? can only be used on Ok and Err, got {}", v),
+                question.line,
+            )),
         }
     }
 
+    // Loops evaluate to nil. The break that ended them must not reach the block around the loop
     fn interp_whileexpr(
         &mut self,
         conditon: Box<Expression>,
         body: Box<Expression>,
-    ) -> Result<Value, String> {
-        let mut last = Value::Break;
+    ) -> Result<Value, ErrorValue> {
         while let Value::Bool(v) = self.interp_expression(*conditon.clone())? {
             if let true = v {
-                last = self.interp_expression(*body.clone())?;
+                let last = self.interp_expression(*body.clone())?;
+                if self.return_val.is_some() {
+                    break;
+                }
                 match last {
                     Value::Break => break,
                     Value::Continue => continue,
                     _ => (),
                 }
             } else {
-                return Ok(last);
+                break;
             }
         }
-        return Ok(last);
+        return Ok(Value::Nil);
     }
 
-    fn interp_loopexpr(&mut self, body: Box<Expression>) -> Result<Value, String> {
-        let mut last = Value::Break;
+    fn interp_loopexpr(&mut self, body: Box<Expression>) -> Result<Value, ErrorValue> {
         loop {
-            last = self.interp_expression(*body.clone())?;
+            let last = self.interp_expression(*body.clone())?;
+            if self.return_val.is_some() {
+                break;
+            }
             match last {
                 Value::Break => break,
                 Value::Continue => {
@@ -535,50 +1712,462 @@ impl Interpreter {
                 _ => (),
             }
         }
-        return Ok(last);
+        return Ok(Value::Nil);
     }
 
-    pub fn look_up(&self, sym : Symbol) -> Result<Value,String>{
+    // Each iteration gets a fresh scope holding the loop variable
+    fn interp_forexpr(
+        &mut self,
+        var: Symbol,
+        iterable: Expression,
+        body: Expression,
+    ) -> Result<Value, ErrorValue> {
+        let iterable = self.interp_expression(iterable)?;
+        for item in self.iterate(iterable).map_err(|e| e.at(var.line))? {
+            let enclosing = self.program_scope.clone();
+            self.program_scope = Scope::new(Some(Box::new(enclosing.clone())));
+            self.program_scope.define_var(var.clone(), item);
+            let last = self.interp_expression(body.clone());
+            self.program_scope = enclosing;
+            if self.return_val.is_some() {
+                break;
+            }
+            if let Value::Break = last? {
+                break;
+            }
+        }
+        Ok(Value::Nil)
+    }
+
+    // The values a for loop walks through. Ranges are produced lazily
+    fn iterate(&self, iterable: Value) -> Result<Box<dyn Iterator<Item = Value>>, ErrorValue> {
+        match iterable {
+            Value::Range(r) => Ok(Box::new(r.map(Value::Int))),
+            Value::List(l) => Ok(Box::new(l.borrow().clone().into_iter())),
+            Value::Tuple(t) => Ok(Box::new(Rc::unwrap_or_clone(t).into_iter())),
+            Value::Dict(d) => Ok(Box::new(d.borrow().keys().into_iter())),
+            Value::String(s) => Ok(Box::new(
+                s.chars()
+                    .map(|c| Value::String(c.to_string()))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )),
+            v => Err(ErrorValue::new("TypeError", format!("Cant iterate over {}", v), 0)),
+        }
+    }
+
+    pub fn look_up(&self, sym : Symbol) -> Result<Value, ErrorValue>{
         let distance = self.lex_scope.get(&sym.s_id);
         if let Some(d) = distance {
-            return Ok(self.program_scope.get_at(sym,*d));
+            return self.program_scope.get_at(sym,*d);
         }else{
-            return Ok(self.global.get_at(sym,0));
+            return self.global.get_at(sym,0);
         }
     }
 }
 
-fn match_callable(interpreter: &mut Interpreter, val: Value) -> Option<Box<dyn Callable>> {
-    match val {
-        Value::NativeFunction(f) => Some(Box::new(f)),
-        Value::Function(f) => {
-            let function = interpreter.function_map.get(&f)?.clone();
-            Some(Box::new(function))
+// print(a, b, c) prints its arguments separated by spaces
+fn join_args(args: &[Value]) -> String {
+    args.iter()
+        .map(|a| a.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The float as an Int when it is a whole number that fits in one
+pub fn as_int(n: f64) -> Option<i64> {
+    match n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64 {
+        true => Some(n as i64),
+        false => None,
+    }
+}
+
+fn is_arithmetic(op: &TokenType) -> bool {
+    matches!(
+        op,
+        TokenType::Plus
+            | TokenType::Minus
+            | TokenType::Aster
+            | TokenType::Slash
+            | TokenType::SlashSlash
+            | TokenType::Percent
+            | TokenType::AsterAster
+            | TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+    )
+}
+
+/*Arithmetic on two Ints. Anything that doesn't fit in an i64 is worked out again as BigInts.
+/ always makes a float so 7 / 2 is 3.5, // keeps it whole. A negative power is a float too */
+fn int_op(l: i64, operation: &Token, r: i64) -> Result<Value, ErrorValue> {
+    let line = operation.line;
+    let result = match operation.token_type {
+        TokenType::Plus => l.checked_add(r),
+        TokenType::Minus => l.checked_sub(r),
+        TokenType::Aster => l.checked_mul(r),
+        TokenType::Slash if r == 0 => {
+            return Err(ErrorValue::new("ZeroDivisionError", "Divide by zero", line))
         }
-        _ => panic!("Error matching callable"),
+        TokenType::Slash => return Ok(Value::Number(l as f64 / r as f64)),
+        TokenType::SlashSlash if r == 0 => {
+            return Err(ErrorValue::new("ZeroDivisionError", "Floor division by zero", line))
+        }
+        TokenType::SlashSlash => floor_div(l, r),
+        TokenType::Percent if r == 0 => {
+            return Err(ErrorValue::new("ZeroDivisionError", "Modulo by zero", line))
+        }
+        TokenType::Percent => Some(floor_mod(l, r)),
+        TokenType::AsterAster if r < 0 => return Ok(Value::Number((l as f64).powf(r as f64))),
+        TokenType::AsterAster => u32::try_from(r).ok().and_then(|r| l.checked_pow(r)),
+        TokenType::Less => return Ok(Value::Bool(l < r)),
+        TokenType::LessEqual => return Ok(Value::Bool(l <= r)),
+        TokenType::Greater => return Ok(Value::Bool(l > r)),
+        TokenType::GreaterEqual => return Ok(Value::Bool(l >= r)),
+        _ => unreachable!("{:?} isn't arithmetic", operation.token_type),
+    };
+    match result {
+        Some(n) => Ok(Value::Int(n)),
+        None => big_op(BigInt::from(l), operation, BigInt::from(r)),
+    }
+}
+
+fn is_bitwise(op: &TokenType) -> bool {
+    matches!(
+        op,
+        TokenType::Ampersand
+            | TokenType::Pipe
+            | TokenType::Caret
+            | TokenType::LessLess
+            | TokenType::GreaterGreater
+    )
+}
+
+/*& | ^ never overflow. Shifting left past the end of an i64 carries on as a BigInt
+and shifting right by 64 or more leaves 0, or -1 for negative numbers */
+fn int_bitwise(l: i64, operation: &Token, r: i64) -> Result<Value, ErrorValue> {
+    match operation.token_type {
+        TokenType::Ampersand => Ok(Value::Int(l & r)),
+        TokenType::Pipe => Ok(Value::Int(l | r)),
+        TokenType::Caret => Ok(Value::Int(l ^ r)),
+        TokenType::LessLess if (0..64).contains(&r) && (l << r) >> r == l => Ok(Value::Int(l << r)),
+        TokenType::GreaterGreater if (0..64).contains(&r) => Ok(Value::Int(l >> r)),
+        TokenType::GreaterGreater if r >= 64 => Ok(Value::Int(if l < 0 { -1 } else { 0 })),
+        _ => big_bitwise(BigInt::from(l), operation, BigInt::from(r)),
+    }
+}
+
+fn big_bitwise(l: BigInt, operation: &Token, r: BigInt) -> Result<Value, ErrorValue> {
+    let line = operation.line;
+    let shift = match operation.token_type {
+        TokenType::Ampersand => return Ok(int_value(&l & &r)),
+        TokenType::Pipe => return Ok(int_value(&l | &r)),
+        TokenType::Caret => return Ok(int_value(&l ^ &r)),
+        _ if r.is_negative() => {
+            return Err(ErrorValue::new(
+                "ValueError",
+                format!("Cant shift by a negative amount ({})", r),
+                line,
+            ))
+        }
+        _ => r.to_u32().unwrap_or(u32::MAX) as usize,
+    };
+    match operation.token_type {
+        TokenType::GreaterGreater => Ok(int_value(&l >> shift)),
+        _ if l.is_zero() => Ok(Value::Int(0)),
+        // The same limit as ** so a typo cant eat all the memory
+        _ if l.bits() + shift as u64 > 1 << 20 => Err(ErrorValue::new(
+            "OverflowError",
+            format!("{} << {} is too big to work out", l, r),
+            line,
+        )),
+        _ => Ok(int_value(&l << shift)),
+    }
+}
+
+// Arithmetic once an Int has gotten too big for an i64. Results that fit go back to being Ints
+fn big_op(l: BigInt, operation: &Token, r: BigInt) -> Result<Value, ErrorValue> {
+    let line = operation.line;
+    match operation.token_type {
+        TokenType::Plus => Ok(int_value(&l + &r)),
+        TokenType::Minus => Ok(int_value(&l - &r)),
+        TokenType::Aster => Ok(int_value(&l * &r)),
+        TokenType::Slash => match l.div_f64(&r) {
+            Some(n) => Ok(Value::Number(n)),
+            None => Err(ErrorValue::new("ZeroDivisionError", "Divide by zero", line)),
+        },
+        TokenType::SlashSlash => match l.div_mod_floor(&r) {
+            Some((q, _)) => Ok(int_value(q)),
+            None => Err(ErrorValue::new("ZeroDivisionError", "Floor division by zero", line)),
+        },
+        TokenType::Percent => match l.div_mod_floor(&r) {
+            Some((_, m)) => Ok(int_value(m)),
+            None => Err(ErrorValue::new("ZeroDivisionError", "Modulo by zero", line)),
+        },
+        TokenType::AsterAster if r.is_negative() => {
+            Ok(Value::Number(l.to_f64().powf(r.to_f64())))
+        }
+        TokenType::AsterAster => match int_pow(&l, &r) {
+            Some(n) => Ok(n),
+            None => Err(ErrorValue::new(
+                "OverflowError",
+                format!("{} ** {} is too big to work out", l, r),
+                line,
+            )),
+        },
+        TokenType::Less => Ok(Value::Bool(l < r)),
+        TokenType::LessEqual => Ok(Value::Bool(l <= r)),
+        TokenType::Greater => Ok(Value::Bool(l > r)),
+        TokenType::GreaterEqual => Ok(Value::Bool(l >= r)),
+        _ => unreachable!("{:?} isn't arithmetic", operation.token_type),
+    }
+}
+
+// None when the answer would take more than a million bits to hold
+fn int_pow(base: &BigInt, pow: &BigInt) -> Option<Value> {
+    let pow = pow.to_u32()?;
+    if base.bits() * pow as u64 > 1 << 20 {
+        return None;
+    }
+    Some(int_value(base.pow(pow)))
+}
+
+// An Int when it fits in an i64, otherwise a BigInt. Users never see which one they have
+pub fn int_value(n: BigInt) -> Value {
+    match n.to_i64() {
+        Some(n) => Value::Int(n),
+        None => Value::BigInt(Rc::new(n)),
+    }
+}
+
+/*Orders two numbers of any kind. None if either isn't a number. Comparing instead of converting
+lets min and max give back the argument itself so Ints stay Ints */
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a.as_bigint(), b.as_bigint()) {
+        (Some(a), Some(b)) => Some(a.cmp(&b)),
+        (Some(a), None) => compare_int_float(&a, b.as_f64()?),
+        (None, Some(b)) => compare_int_float(&b, a.as_f64()?).map(Ordering::reverse),
+        _ => a.as_f64()?.partial_cmp(&b.as_f64()?),
+    }
+}
+
+/*Without rounding the int, so 9007199254740993 > 9007199254740992.0 even though the int turns into
+that float. Every int is between the infinities and none is ordered against NaN */
+fn compare_int_float(i: &BigInt, n: f64) -> Option<Ordering> {
+    if n.is_infinite() {
+        return Some(if n > 0.0 { Ordering::Less } else { Ordering::Greater });
+    }
+    let whole = BigInt::from_f64(n.floor())?;
+    match i.cmp(&whole) {
+        // i is the whole part of n, so it is smaller when n has a fraction
+        Ordering::Equal if n.fract() != 0.0 => Some(Ordering::Less),
+        order => Some(order),
+    }
+}
+
+// Rounds towards negative infinity like floats do. None when it overflows (i64::MIN // -1)
+fn floor_div(l: i64, r: i64) -> Option<i64> {
+    let q = l.checked_div(r)?;
+    match l % r != 0 && (l < 0) != (r < 0) {
+        true => Some(q - 1),
+        false => Some(q),
+    }
+}
+
+// Takes the sign of r so -7 % 2 is 1
+fn floor_mod(l: i64, r: i64) -> i64 {
+    let m = l.wrapping_rem(r);
+    match m != 0 && (m < 0) != (r < 0) {
+        true => m + r,
+        false => m,
+    }
+}
+
+// Every step-th item from start up to end
+fn slice(object: Value, start: Option<Value>, end: Option<Value>, step: usize, line: usize) -> Result<Value, ErrorValue> {
+    match object {
+        Value::List(l) => {
+            let l = l.borrow();
+            let (a, b) = list::slice_bounds(start, end, l.len(), line)?;
+            Ok(Value::List(Rc::new(RefCell::new(l[a..b].iter().step_by(step).cloned().collect()))))
+        }
+        Value::Tuple(t) => {
+            let (a, b) = list::slice_bounds(start, end, t.len(), line)?;
+            Ok(Value::Tuple(Rc::new(t[a..b].iter().step_by(step).cloned().collect())))
+        }
+        Value::String(s) => {
+            let chars: Vec<char> = s.chars().collect();
+            let (a, b) = list::slice_bounds(start, end, chars.len(), line)?;
+            Ok(Value::String(chars[a..b].iter().step_by(step).collect()))
+        }
+        v => Err(ErrorValue::new("TypeError", format!("Cant slice {}", v), line)),
+    }
+}
+
+/*xs[1..3] // xs[1..=3] // xs[(0..10).step(2)]. Bounds work like slice bounds so they can be negative.
+xs[1..=-1] goes up to and including the last item */
+fn slice_range(object: Value, r: Range, line: usize) -> Result<Value, ErrorValue> {
+    if r.step < 0 {
+        return Err(ErrorValue::new(
+            "ValueError",
+            format!("Cant slice with {}, the step must be positive", r),
+            line,
+        ));
+    }
+    let end = match (r.inclusive, r.end) {
+        (false, end) => Some(Value::Int(end)),
+        (true, -1) => None,
+        (true, end) => end.checked_add(1).map(Value::Int),
+    };
+    slice(object, Some(Value::Int(r.start)), end, r.step as usize, line)
+}
+
+// Ranges count with i64s so a big int cant be a bound or step
+fn range_too_big(what: &str, n: &BigInt, line: usize) -> ErrorValue {
+    ErrorValue::new(
+        "OverflowError",
+        format!("Range {} {} is too big, it has to fit in 64 bits", what, n),
+        line,
+    )
+}
+
+/*x in xs. Ranges and lists look for an equal item, dicts for a key and strings for a substring.
+A whole float can be in a range since 2.0 == 2 */
+fn contains(container: &Value, item: &Value) -> Result<bool, ErrorValue> {
+    match (container, item) {
+        (Value::Range(r), Value::Int(n)) => Ok(r.contains(*n)),
+        (Value::Range(r), Value::Number(n)) => Ok(as_int(*n).is_some_and(|n| r.contains(n))),
+        (Value::Range(_), _) => Ok(false),
+        (Value::List(l), item) => Ok(l.borrow().contains(item)),
+        (Value::Tuple(t), item) => Ok(t.contains(item)),
+        (Value::Dict(d), key) => Ok(d.borrow().contains(key)),
+        (Value::String(s), Value::String(sub)) => Ok(s.contains(sub.as_str())),
+        (c, item) => Err(ErrorValue::new(
+            "TypeError",
+            format!("Cant look for {} in {}", item.type_name(), c.type_name()),
+            0,
+        )),
+    }
+}
+
+fn match_callable(interpreter: &mut Interpreter, val: Value) -> Result<Box<dyn Callable>, ErrorValue> {
+    match val {
+        Value::NativeFunction(f) => Ok(Box::new(f)),
+        Value::Function(f) => match interpreter.function_map.get(&f) {
+            Some(function) => Ok(Box::new(function.clone())),
+            None => Err(ErrorValue::new(
+                "RuntimeError",
+                format!("Function {} no longer exists", f),
+                0,
+            )),
+        },
+        Value::BoundMethod(m) => Ok(Box::new((*m).clone())),
+        Value::NativeMethod(m) => Ok(Box::new((*m).clone())),
+        Value::VariantConstructor(c) => Ok(Box::new((*c).clone())),
+        v => Err(ErrorValue::new("TypeError", format!("{} is not callable", v.type_name()), 0)),
     }
 }
 
 #[derive(Clone)]
 pub enum Value {
-    Number(f64),
+    Int(i64),
+    BigInt(Rc<BigInt>), // Ints too big for an i64
+    Number(f64),        // Floats
     String(String),
     Bool(bool),
     NativeFunction(NativeFunction),
     Function(u64),
+    StructDef(Rc<StructDef>),
+    Struct(Rc<RefCell<StructInstance>>),
+    BoundMethod(Rc<BoundMethod>),
+    NativeMethod(Rc<NativeMethod>), // Methods of builtin values like range.step
+    Range(Range),
+    List(Rc<RefCell<Vec<Value>>>), // Lists are shared, every alias sees pushes and index assignments
+    Dict(Rc<RefCell<Dict>>),
+    Tuple(Rc<Vec<Value>>), // Tuples can't be changed after they are made
+    Enum(Rc<EnumDef>),
+    Variant(Rc<Variant>),
+    VariantConstructor(Rc<VariantConstructor>),
+    Error(Rc<ErrorValue>),
+    Module(Rc<LoadedModule>),
     Nil,
     Break,
     Continue,
 }
 
+impl Value {
+    // Ints and floats as a float, for natives that take either
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(n) => Some(*n as f64),
+            Value::BigInt(n) => Some(n.to_f64()),
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bigint(&self) -> Option<BigInt> {
+        match self {
+            Value::Int(n) => Some(BigInt::from(*n)),
+            Value::BigInt(n) => Some((**n).clone()),
+            _ => None,
+        }
+    }
+
+    // Used by error messages. 1 + "a" // Cant use + on number and string
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) | Value::BigInt(_) => "int",
+            Value::Number(_) => "float",
+            Value::String(_) => "string",
+            Value::Bool(_) => "bool",
+            Value::NativeFunction(_)
+            | Value::Function(_)
+            | Value::BoundMethod(_)
+            | Value::NativeMethod(_) => "function",
+            Value::StructDef(def) if def.is_class => "class",
+            Value::StructDef(_) => "struct",
+            Value::Struct(_) => "instance",
+            Value::Range(_) => "range",
+            Value::List(_) => "list",
+            Value::Dict(_) => "dict",
+            Value::Tuple(_) => "tuple",
+            Value::Enum(_) => "enum",
+            Value::Variant(_) | Value::VariantConstructor(_) => "variant",
+            Value::Error(_) => "error",
+            Value::Module(_) => "module",
+            Value::Nil => "nil",
+            Value::Break | Value::Continue => "control flow",
+        }
+    }
+}
+
 impl Debug for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Int(arg0) => f.debug_tuple("Int").field(arg0).finish(),
+            Self::BigInt(arg0) => f.debug_tuple("BigInt").field(arg0).finish(),
             Self::Number(arg0) => f.debug_tuple("Number").field(arg0).finish(),
             Self::String(arg0) => f.debug_tuple("String").field(arg0).finish(),
             Self::Bool(arg0) => f.debug_tuple("Bool").field(arg0).finish(),
             Self::NativeFunction(arg0) => f.debug_tuple("NativeFunction").field(arg0).finish(),
             Self::Function(arg0) => f.debug_tuple("Function").field(arg0).finish(),
+            Self::StructDef(arg0) => f.debug_tuple("StructDef").field(arg0).finish(),
+            Self::Struct(arg0) => f.debug_tuple("Struct").field(&arg0.borrow()).finish(),
+            Self::BoundMethod(arg0) => f.debug_tuple("BoundMethod").field(arg0).finish(),
+            Self::NativeMethod(arg0) => f.debug_tuple("NativeMethod").field(arg0).finish(),
+            Self::Range(arg0) => f.debug_tuple("Range").field(arg0).finish(),
+            Self::List(arg0) => f.debug_tuple("List").field(&arg0.borrow()).finish(),
+            Self::Dict(arg0) => f.debug_tuple("Dict").field(&arg0.borrow()).finish(),
+            Self::Tuple(arg0) => f.debug_tuple("Tuple").field(arg0).finish(),
+            Self::Enum(arg0) => f.debug_tuple("Enum").field(arg0).finish(),
+            Self::Variant(arg0) => f.debug_tuple("Variant").field(arg0).finish(),
+            Self::VariantConstructor(arg0) => {
+                f.debug_tuple("VariantConstructor").field(arg0).finish()
+            }
+            Self::Error(arg0) => f.debug_tuple("Error").field(arg0).finish(),
+            Self::Module(arg0) => f.debug_tuple("Module").field(arg0).finish(),
             Self::Nil => write!(f, "Nil"),
             Self::Break => write!(f, "Break"),
             Self::Continue => write!(f, "Continue"),
@@ -586,9 +2175,36 @@ impl Debug for Value {
     }
 }
 
+thread_local! {
+    // Lists, dicts and structs that are being printed. Finding one of them again inside itself means it holds itself
+    static PRINTING: RefCell<Vec<usize>> = const { RefCell::new(vec![]) };
+}
+
+// let xs = [1]; push(xs, xs); prints [1, [...]] instead of going around forever
+fn print_once(
+    f: &mut std::fmt::Formatter<'_>,
+    container: usize,
+    repeat: &str,
+    print: impl FnOnce(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+) -> std::fmt::Result {
+    if PRINTING.with(|p| p.borrow().contains(&container)) {
+        return f.write_str(repeat);
+    }
+    PRINTING.with(|p| p.borrow_mut().push(container));
+    let result = print(f);
+    PRINTING.with(|p| p.borrow_mut().pop());
+    result
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Value::Int(n) => f.write_fmt(format_args!("{}", n)),
+            Value::BigInt(n) => f.write_fmt(format_args!("{}", n)),
+            // Whole floats keep their .0 so they can be told apart from Ints
+            Value::Number(n) if n.is_finite() && n.fract() == 0.0 => {
+                f.write_fmt(format_args!("{:.1}", n))
+            }
             Value::Number(n) => f.write_fmt(format_args!("{}", n)),
             Value::String(s) => f.write_fmt(format_args!("{}", s)),
             Value::Bool(b) => f.write_fmt(format_args!("{}", b)),
@@ -597,6 +2213,125 @@ impl Display for Value {
             Value::Break => todo!(),
             Value::Continue => todo!(),
             Value::Function(fu) => f.write_fmt(format_args!("{}", fu)),
+            Value::StructDef(def) if def.is_class => {
+                f.write_fmt(format_args!("<class {}>", def.name))
+            }
+            Value::StructDef(def) => f.write_fmt(format_args!("<struct {}>", def.name)),
+            Value::Struct(instance) => {
+                let repeat = format!("{} {{...}}", instance.borrow().def.name);
+                print_once(f, Rc::as_ptr(instance) as usize, &repeat, |f| {
+                    f.write_fmt(format_args!("{}", instance.borrow()))
+                })
+            }
+            Value::BoundMethod(m) => f.write_fmt(format_args!("<method {}>", m.name)),
+            Value::NativeMethod(m) => f.write_fmt(format_args!("<method {}>", m.native.name)),
+            Value::Range(r) => f.write_fmt(format_args!("{}", r)),
+            Value::List(l) => print_once(f, Rc::as_ptr(l) as usize, "[...]", |f| {
+                f.write_str("[")?;
+                for (i, v) in l.borrow().iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_fmt(format_args!("{}", v))?;
+                }
+                f.write_str("]")
+            }),
+            Value::Dict(d) => print_once(f, Rc::as_ptr(d) as usize, "{...}", |f| {
+                f.write_fmt(format_args!("{}", d.borrow()))
+            }),
+            Value::Enum(def) => f.write_fmt(format_args!("<enum {}>", def.name)),
+            Value::Variant(v) => f.write_fmt(format_args!("{}", v)),
+            Value::VariantConstructor(c) => f.write_fmt(format_args!("<variant {}>", c.name())),
+            Value::Error(e) => f.write_fmt(format_args!("{}", e)),
+            Value::Module(m) => f.write_fmt(format_args!("<module {}>", m.name)),
+            Value::Tuple(t) => {
+                f.write_str("(")?;
+                for (i, v) in t.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_fmt(format_args!("{}", v))?;
+                }
+                if t.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/*Lists, dictionaries and structs are equal when what they hold is equal.
+Functions and struct definitions are only equal to themselves */
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Number(l), Value::Number(r)) => l == r,
+            (Value::Int(l), Value::Int(r)) => l == r,
+            // 1 == 1.0. Compared exactly so big Ints aren't equal to floats they round to
+            (Value::Int(i), Value::Number(n)) | (Value::Number(n), Value::Int(i)) => {
+                as_int(*n) == Some(*i)
+            }
+            (Value::BigInt(l), Value::BigInt(r)) => l == r,
+            (Value::BigInt(b), Value::Number(n)) | (Value::Number(n), Value::BigInt(b)) => {
+                BigInt::from_f64(*n).as_ref() == Some(&**b)
+            }
+            (Value::String(l), Value::String(r)) => l == r,
+            (Value::Bool(l), Value::Bool(r)) => l == r,
+            (Value::Nil, Value::Nil) => true,
+            (Value::List(l), Value::List(r)) => Rc::ptr_eq(l, r) || *l.borrow() == *r.borrow(),
+            (Value::Dict(l), Value::Dict(r)) => Rc::ptr_eq(l, r) || *l.borrow() == *r.borrow(),
+            (Value::Tuple(l), Value::Tuple(r)) => l == r,
+            (Value::Variant(l), Value::Variant(r)) => l == r,
+            (Value::Enum(l), Value::Enum(r)) => Rc::ptr_eq(l, r),
+            (Value::Error(l), Value::Error(r)) => l == r,
+            (Value::Module(l), Value::Module(r)) => Rc::ptr_eq(l, r),
+            (Value::Struct(l), Value::Struct(r)) => {
+                let (l, r) = (l.borrow(), r.borrow());
+                Rc::ptr_eq(&l.def, &r.def) && l.values == r.values
+            }
+            (Value::StructDef(l), Value::StructDef(r)) => Rc::ptr_eq(l, r),
+            (Value::Function(l), Value::Function(r)) => l == r,
+            (Value::NativeFunction(l), Value::NativeFunction(r)) => l.name == r.name,
+            (Value::BoundMethod(l), Value::BoundMethod(r)) => {
+                l.f_id == r.f_id && l.receiver == r.receiver
+            }
+            (Value::NativeMethod(l), Value::NativeMethod(r)) => {
+                l.native.name == r.native.name && l.receiver == r.receiver
+            }
+            (Value::Range(l), Value::Range(r)) => l == r,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+// Only dictionary keys (strings, numbers, bools, nil and tuples of them) hash their contents
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Whole floats are equal to Ints so they must hash the same. This covers 0.0 and -0.0 too
+        if let Value::Number(n) = self {
+            if let Some(i) = as_int(*n) {
+                return Value::Int(i).hash(state);
+            }
+            if let Some(b) = BigInt::from_f64(*n) {
+                return Value::BigInt(Rc::new(b)).hash(state);
+            }
+        }
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Int(i) => i.hash(state),
+            Value::BigInt(b) => b.hash(state),
+            Value::Number(n) => n.to_bits().hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Bool(b) => b.hash(state),
+            Value::Tuple(t) => t.hash(state),
+            Value::Variant(v) => {
+                v.name().hash(state);
+                v.values.hash(state);
+            }
+            _ => (),
         }
     }
 }
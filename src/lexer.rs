@@ -1,27 +1,39 @@
 use std::{collections::HashMap, vec};
 
 use crate::{
+    bigint::BigInt,
     token::{Literal, TokenType},
     Token,
 };
 
 pub struct Lexer {
-    source: String,
+    source: Vec<char>, // Chars so start and current can index it even with non ascii text
     pub tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
     keywords: HashMap<String, TokenType>,
+    /*One entry for each string interpolation currently being lexed, counting the { opened inside it.
+    The } that closes the interpolation is the one found when the count is 0.
+    The quote is kept so the string carries on the same way after the interpolation */
+    interpolation: Vec<(usize, Quote)>,
+}
+
+#[derive(Clone, Copy)]
+enum Quote {
+    Single,
+    Triple(usize), // """ Holds the indentation stripped from every line
 }
 
 impl Lexer {
-    pub fn new(src: &String) -> Lexer {
+    pub fn new(src: &str) -> Lexer {
         let mut _keywords = HashMap::new();
         _keywords.insert("and".to_string(), TokenType::And);
         _keywords.insert("class".to_string(), TokenType::Class);
         _keywords.insert("else".to_string(), TokenType::Else);
         _keywords.insert("false".to_string(), TokenType::False);
         _keywords.insert("for".to_string(), TokenType::For);
+        _keywords.insert("in".to_string(), TokenType::In);
         _keywords.insert("fun".to_string(), TokenType::Fun);
         _keywords.insert("loop".to_string(), TokenType::Loop);
         _keywords.insert("if".to_string(), TokenType::If);
@@ -37,18 +49,28 @@ impl Lexer {
         _keywords.insert("break".to_string(), TokenType::Break);
         _keywords.insert("continue".to_string(), TokenType::Continue);
         _keywords.insert("struct".to_string(), TokenType::Struct);
+        _keywords.insert("enum".to_string(), TokenType::Enum);
+        _keywords.insert("impl".to_string(), TokenType::Impl);
+        _keywords.insert("match".to_string(), TokenType::Match);
+        _keywords.insert("try".to_string(), TokenType::Try);
+        _keywords.insert("catch".to_string(), TokenType::Catch);
+        _keywords.insert("finally".to_string(), TokenType::Finally);
+        _keywords.insert("throw".to_string(), TokenType::Throw);
+        _keywords.insert("import".to_string(), TokenType::Import);
+        _keywords.insert("const".to_string(), TokenType::Const);
         Lexer {
-            source: src.to_string(),
+            source: src.chars().collect(),
             tokens: vec![],
             start: 0,
             current: 0,
             line: 1,
             keywords: _keywords,
+            interpolation: vec![],
         }
     }
 
     fn is_at_end(&self) -> bool {
-        self.current > self.source.len() - 1
+        self.current >= self.source.len()
     }
 
     pub fn scan_tokens(&mut self) {
@@ -56,6 +78,9 @@ impl Lexer {
             self.start = self.current;
             self.scan_token();
         }
+        if !self.interpolation.is_empty() {
+            panic!("[Lexer Error] Unterminated string @Line {}", self.line)
+        }
         self.tokens.push(Token {
             token_type: TokenType::TERMINATE,
             literal: None,
@@ -71,16 +96,86 @@ impl Lexer {
         match c {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftSquigly),
-            '}' => self.add_token(TokenType::RightSquigly),
-            '+' => self.add_token(TokenType::Plus),
-            '-' => self.add_token(TokenType::Minus),
-            '*' => self.add_token(TokenType::Aster),
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
+            '{' => {
+                if let Some((depth, _)) = self.interpolation.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftSquigly)
+            }
+            '}' => match self.interpolation.last_mut() {
+                // End of an interpolated expression, carry on with the rest of the string
+                Some((0, quote)) => {
+                    let quote = *quote;
+                    self.interpolation.pop();
+                    self.string(quote);
+                }
+                Some((depth, _)) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightSquigly)
+                }
+                None => self.add_token(TokenType::RightSquigly),
+            },
+            '+' => {
+                if self.matcher('=') {
+                    self.add_token(TokenType::PlusEqual)
+                } else {
+                    self.add_token(TokenType::Plus)
+                }
+            }
+            '-' => {
+                if self.matcher('=') {
+                    self.add_token(TokenType::MinusEqual)
+                } else if self.matcher('>') {
+                    self.add_token(TokenType::Arrow)
+                } else {
+                    self.add_token(TokenType::Minus)
+                }
+            }
+            '*' => {
+                if self.matcher('*') {
+                    self.add_token(TokenType::AsterAster)
+                } else if self.matcher('=') {
+                    self.add_token(TokenType::AsterEqual)
+                } else {
+                    self.add_token(TokenType::Aster)
+                }
+            }
+            '%' => {
+                if self.matcher('=') {
+                    self.add_token(TokenType::PercentEqual)
+                } else {
+                    self.add_token(TokenType::Percent)
+                }
+            }
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
+            '.' => {
+                if self.matcher('.') {
+                    if self.matcher('=') {
+                        self.add_token(TokenType::DotDotEqual)
+                    } else {
+                        self.add_token(TokenType::DotDot)
+                    }
+                } else {
+                    self.add_token(TokenType::Dot)
+                }
+            }
             ';' => self.add_token(TokenType::Semicolon),
-            '/' => self.add_token(TokenType::Slash),
+            '/' => {
+                if self.matcher('/') {
+                    self.add_token(TokenType::SlashSlash)
+                } else if self.matcher('=') {
+                    self.add_token(TokenType::SlashEqual)
+                } else {
+                    self.add_token(TokenType::Slash)
+                }
+            }
             '?' => self.add_token(TokenType::Ternary),
+            '|' => self.add_token(TokenType::Pipe),
+            '&' => self.add_token(TokenType::Ampersand),
+            '^' => self.add_token(TokenType::Caret),
+            '~' => self.add_token(TokenType::Tilde),
             ':' => self.add_token(TokenType::Colon),
             '!' => {
                 if self.matcher('=') {
@@ -90,21 +185,27 @@ impl Lexer {
                 };
             }
             '=' => {
-                if self.matcher('=') {
+                if self.matcher('>') {
+                    self.add_token(TokenType::FatArrow)
+                } else if self.matcher('=') {
                     self.add_token(TokenType::Equality)
                 } else {
                     self.add_token(TokenType::Assignment)
                 }
             }
             '<' => {
-                if self.matcher('=') {
+                if self.matcher('<') {
+                    self.add_token(TokenType::LessLess)
+                } else if self.matcher('=') {
                     self.add_token(TokenType::LessEqual)
                 } else {
                     self.add_token(TokenType::Less)
                 }
             }
             '>' => {
-                if self.matcher('=') {
+                if self.matcher('>') {
+                    self.add_token(TokenType::GreaterGreater)
+                } else if self.matcher('=') {
                     self.add_token(TokenType::GreaterEqual)
                 } else {
                     self.add_token(TokenType::Greater)
@@ -116,22 +217,17 @@ impl Lexer {
                     self.advance();
                 }
             }
-            'o' => {
-                if self.peek() == 'r' {
-                    self.add_token(TokenType::Or);
-                    self.advance();
-                }
-            }
-            'a' => {
-                if self.peek() == 'n' && self.peek_next() == 'd' {
-                    self.add_token(TokenType::And);
+            '"' => {
+                if self.peek() == '"' && self.peek_next() == '"' {
                     self.advance();
                     self.advance();
+                    let indent = self.triple_quote_start();
+                    self.string(Quote::Triple(indent))
                 } else {
-                    self.identifier();
+                    self.string(Quote::Single)
                 }
             }
-            '"' => self.string(),
+            'r' if self.peek() == '"' => self.raw_string(),
             ' ' | '\r' | '\t' => {} // Do nothing with white space
             '\n' => self.line += 1,
             _ => {
@@ -146,25 +242,149 @@ impl Lexer {
         }
     }
 
-    fn string(&mut self) {
-        while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1
-            };
-            self.advance();
+    /*"a {x} b" is lexed as Interpolation("a ") Identifier(x) String(" b")
+    The tokens of the expression inside the { } are lexed normally and then the string carries on.
+    {{ and }} are a literal { and } */
+    fn string(&mut self, quote: Quote) {
+        let line = self.line;
+        let mut value = String::new();
+        loop {
+            if self.is_at_end() {
+                panic!("[Lexer Error] Unterminated string @Line {}", line)
+            }
+            let c = self.advance();
+            match (c, quote) {
+                ('"', Quote::Single) => break,
+                ('"', Quote::Triple(_)) if self.peek() == '"' && self.peek_next() == '"' => {
+                    self.advance();
+                    self.advance();
+                    trim_last_line(&mut value);
+                    break;
+                }
+                ('\n', _) => {
+                    self.line += 1;
+                    value.push(c);
+                    if let Quote::Triple(indent) = quote {
+                        self.skip_indent(indent);
+                    }
+                }
+                ('\\', _) => value.push(self.escape()),
+                ('{', _) if self.peek() == '{' => {
+                    self.advance();
+                    value.push(c);
+                }
+                ('}', _) if self.peek() == '}' => {
+                    self.advance();
+                    value.push(c);
+                }
+                ('{', _) => {
+                    self.interpolation.push((0, quote));
+                    self.add_literal_token(TokenType::Interpolation, Some(Literal::Str(value)));
+                    return;
+                }
+                _ => value.push(c),
+            }
         }
+        self.add_literal_token(TokenType::String, Some(Literal::Str(value)));
+    }
+
+    // The character after a \ in a string
+    fn escape(&mut self) -> char {
         if self.is_at_end() {
-            //Error unterminated string
-            return;
+            panic!("[Lexer Error] Unterminated string @Line {}", self.line)
+        }
+        match self.advance() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            'u' => self.unicode_escape(),
+            c => panic!(
+                "[Lexer Error] Invalid escape sequence \\{} @Line {}",
+                c, self.line
+            ),
+        }
+    }
+
+    // \u{1F600} Up to 6 hex digits
+    fn unicode_escape(&mut self) -> char {
+        if !self.matcher('{') {
+            panic!("[Lexer Error] Expect '{{' after \\u @Line {}", self.line)
+        }
+        let mut hex = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            hex.push(self.advance());
         }
+        if !self.matcher('}') || hex.is_empty() || hex.len() > 6 {
+            panic!(
+                "[Lexer Error] Invalid unicode escape \\u{{{}... @Line {}",
+                hex, self.line
+            )
+        }
+        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+            Some(c) => c,
+            None => panic!(
+                "[Lexer Error] Invalid unicode character \\u{{{}}} @Line {}",
+                hex, self.line
+            ),
+        }
+    }
+
+    /*Called after the opening """. A newline straight after the quotes isn't part of the string.
+    Returns the smallest indentation of the lines that aren't blank, which is removed from every line */
+    fn triple_quote_start(&mut self) -> usize {
+        let rest = self.text(self.current, self.source.len());
+        let text = match rest.find("\"\"\"") {
+            Some(end) => &rest[..end],
+            None => panic!("[Lexer Error] Unterminated string @Line {}", self.line),
+        };
+        let indent = common_indent(text);
+        if self.peek() == '\n' {
+            self.advance();
+            self.line += 1;
+            self.skip_indent(indent);
+        }
+        indent
+    }
+
+    fn skip_indent(&mut self, indent: usize) {
+        for _ in 0..indent {
+            if self.peek() != ' ' && self.peek() != '\t' {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    // r"C:\path\{x}" Nothing inside a raw string is escaped or interpolated
+    fn raw_string(&mut self) {
+        let line = self.line;
         self.advance();
-        let value: String = self.source[self.start + 1..self.current - 1].to_string();
+        let triple = self.peek() == '"' && self.peek_next() == '"';
+        let quote = if triple {
+            self.advance();
+            self.advance();
+            "\"\"\""
+        } else {
+            "\""
+        };
+        let rest = self.text(self.current, self.source.len());
+        let text = match rest.find(quote) {
+            Some(end) => rest[..end].to_string(),
+            None => panic!("[Lexer Error] Unterminated string @Line {}", line),
+        };
+        self.current += text.chars().count() + quote.len();
+        self.line += text.matches('\n').count();
+        let value = if triple { dedent(&text) } else { text };
         self.add_literal_token(TokenType::String, Some(Literal::Str(value)));
     }
 
     pub fn advance(&mut self) -> char {
         self.current += 1;
-        return self.source.chars().nth(self.current - 1).unwrap();
+        return self.source[self.current - 1];
     }
 
     // pub fn advance_by(&mut self, n : usize) -> Vec<char>{
@@ -179,14 +399,14 @@ impl Lexer {
         if self.is_at_end() {
             return '\0';
         }
-        return self.source.chars().nth(self.current).unwrap();
+        return self.source[self.current];
     }
 
     fn matcher(&mut self, c: char) -> bool {
         if self.is_at_end() {
             return false;
         }
-        if self.source.chars().nth(self.current).unwrap() != c {
+        if self.source[self.current] != c {
             return false;
         }
         self.current += 1;
@@ -198,41 +418,102 @@ impl Lexer {
     }
 
     fn add_literal_token(&mut self, token_type: TokenType, literal: Option<Literal>) {
-        let lex = &self.source[self.start..self.current];
+        let lex = self.text(self.start, self.current);
         self.tokens.push(Token {
             token_type,
             literal,
-            lex: lex.to_string(),
+            lex,
             line: self.line,
         })
     }
 
+    /*Whole numbers are Ints and anything with a . or an exponent is a float. 1_000_000 // 1.5e-9
+    0x, 0o and 0b start hex, octal and binary Ints. Underscores can go anywhere between digits */
     fn number(&mut self) {
-        while self.peek().is_digit(10) {
+        let radix = match (self.source[self.start], self.peek()) {
+            ('0', 'x' | 'X') => 16,
+            ('0', 'o' | 'O') => 8,
+            ('0', 'b' | 'B') => 2,
+            _ => 10,
+        };
+        if radix != 10 {
             self.advance();
+            self.digits(radix);
+            let digits = self.text(self.start + 2, self.current).replace('_', "");
+            if digits.is_empty() {
+                panic!(
+                    "[Lexer Error] Expect digits after {} @Line {}",
+                    self.text(self.start, self.current),
+                    self.line
+                )
+            }
+            return self.int(&digits, radix);
         }
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
+        self.digits(10);
+        let mut float = false;
+        // 1.5 is a float but the dots in 1..5 and 1.x are left for scan_token
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+            float = true;
             self.advance();
-
-            while self.peek().is_digit(10) {
+            self.digits(10);
+        }
+        // 1e9 // 1e-9. An e not followed by digits is left for whatever comes next
+        let sign = matches!(self.peek_next(), '+' | '-') as usize;
+        let after = self
+            .source
+            .get(self.current + 1 + sign)
+            .copied()
+            .unwrap_or('\0');
+        if matches!(self.peek(), 'e' | 'E') && after.is_ascii_digit() {
+            float = true;
+            for _ in 0..=sign {
                 self.advance();
             }
+            self.digits(10);
+        }
+        let text = self.text(self.start, self.current).replace('_', "");
+        if !float {
+            return self.int(&text, 10);
         }
         self.add_literal_token(
             TokenType::Number,
-            Some(Literal::Number(
-                self.source[self.start..self.current]
-                    .parse::<f64>()
-                    .unwrap(),
-            )),
+            Some(Literal::Number(text.parse::<f64>().unwrap())),
         )
     }
 
+    fn digits(&mut self, radix: u32) {
+        while self.peek().is_digit(radix)
+            || (self.peek() == '_' && self.peek_next().is_digit(radix))
+        {
+            self.advance();
+        }
+    }
+
+    // Literals too big for an i64 are BigInts from the start
+    fn int(&mut self, digits: &str, radix: u32) {
+        let literal = match i64::from_str_radix(digits, radix) {
+            Ok(n) => Literal::Int(n),
+            Err(_) => match BigInt::parse(digits, radix) {
+                Some(n) => Literal::BigInt(n),
+                None => panic!(
+                    "[Lexer Error] Invalid number {} @Line {}",
+                    digits, self.line
+                ),
+            },
+        };
+        self.add_literal_token(TokenType::Number, Some(literal))
+    }
+
     fn peek_next(&self) -> char {
         if self.current + 1 >= self.source.len() {
             return '\0';
         }
-        return self.source.chars().nth(self.current + 1).unwrap();
+        return self.source[self.current + 1];
+    }
+
+    // The source between two char positions
+    fn text(&self, from: usize, to: usize) -> String {
+        self.source[from..to].iter().collect()
     }
 
     // fn is_alpha(&self, c: char) -> bool {
@@ -247,8 +528,8 @@ impl Lexer {
         while self.peek().is_alphanumeric() || self.peek() == '_' {
             self.advance();
         }
-        let a = &self.source[self.start..self.current];
-        let t = self.keywords.get(a);
+        let a = self.text(self.start, self.current);
+        let t = self.keywords.get(&a);
         if let Some(istype) = t {
             self.add_token(*istype);
         } else {
@@ -256,3 +537,36 @@ impl Lexer {
         }
     }
 }
+
+fn common_indent(text: &str) -> usize {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0)
+}
+
+// A last line with nothing but the indentation of the closing """ isn't part of the string
+fn trim_last_line(value: &mut String) {
+    if let Some(pos) = value.rfind('\n') {
+        if value[pos + 1..].trim().is_empty() {
+            value.truncate(pos);
+        }
+    }
+}
+
+// What a triple quoted string does to its text, for raw strings which are taken in one go
+fn dedent(text: &str) -> String {
+    let indent = common_indent(text);
+    let text = text.strip_prefix('\n').unwrap_or(text);
+    let mut value = text
+        .split('\n')
+        .map(|l| {
+            let strip = l.len() - l.trim_start_matches([' ', '\t']).len();
+            &l[strip.min(indent)..]
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    trim_last_line(&mut value);
+    value
+}
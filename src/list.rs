@@ -0,0 +1,70 @@
+use crate::{error::ErrorValue, interpreter::Value};
+
+// Turns an index value into a position in a sequence of length len. Negative indexes count from the end
+pub fn index_of(index: &Value, len: usize, line: usize) -> Result<usize, ErrorValue> {
+    let i = match index {
+        Value::Int(n) => *n,
+        Value::Number(n) if n.fract() == 0.0 => *n as i64,
+        Value::BigInt(n) => {
+            return Err(ErrorValue::new(
+                "IndexError",
+                format!("Index {} out of bounds for length {}", n, len),
+                line,
+            ))
+        }
+        v => {
+            return Err(ErrorValue::new(
+                "TypeError",
+                format!("Index must be a whole number, got {}", v),
+                line,
+            ))
+        }
+    };
+    let pos = if i < 0 { i + len as i64 } else { i };
+    if pos < 0 || pos >= len as i64 {
+        return Err(ErrorValue::new(
+            "IndexError",
+            format!("Index {} out of bounds for length {}", i, len),
+            line,
+        ));
+    }
+    Ok(pos as usize)
+}
+
+/*Start and end of a slice [a:b] in a sequence of length len. Missing bounds default to the start and end
+Negative bounds count from the end and bounds past either end are clamped like they are in python */
+pub fn slice_bounds(
+    start: Option<Value>,
+    end: Option<Value>,
+    len: usize,
+    line: usize,
+) -> Result<(usize, usize), ErrorValue> {
+    let start = match start {
+        Some(v) => bound(&v, len, line)?,
+        None => 0,
+    };
+    let end = match end {
+        Some(v) => bound(&v, len, line)?,
+        None => len,
+    };
+    Ok((start, end.max(start)))
+}
+
+fn bound(v: &Value, len: usize, line: usize) -> Result<usize, ErrorValue> {
+    let i = match v {
+        Value::Int(n) => *n,
+        Value::Number(n) if n.fract() == 0.0 => *n as i64,
+        // Past one end or the other
+        Value::BigInt(n) if n.is_negative() => return Ok(0),
+        Value::BigInt(_) => return Ok(len),
+        v => {
+            return Err(ErrorValue::new(
+                "TypeError",
+                format!("Slice bounds must be whole numbers, got {}", v),
+                line,
+            ))
+        }
+    };
+    let pos = if i < 0 { i + len as i64 } else { i };
+    Ok(pos.clamp(0, len as i64) as usize)
+}
@@ -1,31 +1,57 @@
 use std::{fs, path::PathBuf, collections::HashMap};
 
+mod bigint;
+mod checker;
+mod dict;
+mod enumeration;
+mod error;
 mod expression;
 mod function;
 mod interpreter;
 mod lexer;
+mod list;
+mod module;
 mod parser;
+mod pattern;
+mod range;
 mod scope;
 mod statement;
+mod structure;
 mod token;
 mod resolver;
+mod types;
 
-use clap::Parser;
+use checker::Checker;
+use clap::{Parser, Subcommand};
 use interpreter::Interpreter;
 use lexer::Lexer;
 use parser::Parser as scrlt;
 use resolver::Resolver;
+use statement::Statement;
 use token::Token;
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
     path: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    // Type check a file without running it
+    Check { path: PathBuf },
 }
 
 fn main() {
     let args = Args::parse();
-    if let Some(p) = args.path {
+    if let Some(Command::Check { path }) = args.command {
+        let c = fs::read_to_string(&path).expect("Couldn't read file");
+        let (stmts, resolver) = load(c, path.clone());
+        check(&stmts, &resolver);
+        println!("{} has no type errors", path.display());
+    } else if let Some(p) = args.path {
         read_file(p);
     } else {
         println!("Enter");
@@ -44,23 +70,54 @@ fn main() {
 }
 
 fn read_file(path: PathBuf) {
-    let c = fs::read_to_string(path).expect("Couldn't read file");
-    run(c);
+    let c = fs::read_to_string(&path).expect("Couldn't read file");
+    run(c, path);
 }
 
-fn run(src: String) {
+// Lexes, parses and resolves a file along with everything it imports
+fn load(src: String, path: PathBuf) -> (Vec<Statement>, Resolver) {
     let mut scanner = Lexer::new(&src);
     scanner.scan_tokens();
     // scanner.tokens.into_iter().map(|x| print!("{}", x));
     let mut parser: scrlt = scrlt::new(scanner.tokens);
 
     let stmts = parser.parse().expect("Parsing failure");
-    let mut resolver = Resolver::new();
+    let mut resolver = Resolver::new(path, parser.s_id);
 
     resolver.resolve(stmts.clone());
+    (stmts, resolver)
+}
+
+/*Type checks the file and each module it imports. Exits before anything runs if any of them have errors.
+The resolver has already been through every statement so the checker can trust that names exist */
+fn check(stmts: &[Statement], resolver: &Resolver) {
+    let mut checker = Checker::new();
+    checker.check(stmts.to_vec());
+    let mut errors = checker.errors;
+
+    let mut modules: Vec<_> = resolver.imports.values().collect();
+    modules.sort_by(|a, b| a.path.cmp(&b.path));
+    modules.dedup_by(|a, b| a.path == b.path);
+    for module in modules {
+        let mut checker = Checker::new();
+        checker.check(module.stmts.clone());
+        let path = module.path.display();
+        errors.extend(checker.errors.iter().map(|e| format!("In {}: {}", path, e)));
+    }
+    if !errors.is_empty() {
+        for e in errors {
+            eprintln!("{}", e);
+        }
+        std::process::exit(1);
+    }
+}
 
+fn run(src: String, path: PathBuf) {
+    let (stmts, resolver) = load(src, path);
+    check(&stmts, &resolver);
 
     let mut interpreter: Interpreter = Interpreter::new(resolver.lex_scope);
+    interpreter.imports = resolver.imports;
 
 
     // let mut resolver = Resolver::new(&interpreter);
@@ -0,0 +1,91 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{error::ErrorValue, interpreter::Value, statement::Statement};
+
+/*A file brought in by an import. import "util/math.scrlt" as m; // from "util/math" import a, b;
+The resolver reads and resolves each file once, the interpreter runs it the first time it is imported */
+pub struct Module {
+    pub path: PathBuf,
+    pub name: String,
+    pub stmts: Vec<Statement>,
+    pub names: Vec<String>, // Everything declared at the top of the file, these are what can be imported
+    pub constants: HashSet<String>, // The names that were declared with const. They stay const when imported
+}
+
+impl Debug for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Module")
+            .field("path", &self.path)
+            .field("names", &self.names)
+            .field("constants", &self.constants)
+            .finish()
+    }
+}
+
+// A module after it has run. m.a looks up a in the scope the file was run in
+pub struct LoadedModule {
+    pub name: String,
+    pub values: Rc<RefCell<HashMap<String, Value>>>,
+    pub constants: Rc<RefCell<HashSet<String>>>,
+}
+
+impl LoadedModule {
+    pub fn get(&self, name: &str) -> Result<Value, ErrorValue> {
+        match self.values.borrow().get(name) {
+            Some(v) => Ok(v.clone()),
+            None => Err(ErrorValue::new(
+                "AttributeError",
+                format!("Module {} has no {}", self.name, name),
+                0,
+            )),
+        }
+    }
+}
+
+impl Debug for LoadedModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadedModule")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/*Imports are looked up next to the file importing them first and then in each directory of SCARLET_PATH.
+.scrlt is added when the path has no extension */
+pub fn find(path: &str, importer: &Path) -> Result<PathBuf, ErrorValue> {
+    let mut file = PathBuf::from(path);
+    if file.extension().is_none() {
+        file.set_extension("scrlt");
+    }
+    let mut dirs = vec![importer.parent().unwrap_or(Path::new("")).to_path_buf()];
+    if let Ok(search) = std::env::var("SCARLET_PATH") {
+        dirs.extend(std::env::split_paths(&search));
+    }
+    for dir in dirs {
+        let candidate = dir.join(&file);
+        if candidate.is_file() {
+            return candidate
+                .canonicalize()
+                .map_err(|e| ErrorValue::new("ImportError", e.to_string(), 0));
+        }
+    }
+    Err(ErrorValue::new(
+        "ImportError",
+        format!("Cant find module \"{}\"", path),
+        0,
+    ))
+}
+
+// import "util/math.scrlt"; is the same as import "util/math.scrlt" as math;
+pub fn default_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -1,5 +1,8 @@
 use crate::{
-    expression::{Expression, Symbol},
+    bigint::BigInt,
+    expression::{Catch, Expression, MatchArm, Param, Symbol},
+    module,
+    pattern::Pattern,
     statement::Statement,
     token::{Literal, Token, TokenType},
 };
@@ -10,7 +13,10 @@ pub struct Parser {
     current: usize,
     inloop: bool, // Used for break expression
     function_stack: Vec<u8>, //Used to tell if parser is currently inside function declaration
-    s_id : u64,
+    pub s_id : u64, // Last symbol id handed out. Imported files carry on from where the importing file stopped
+    no_struct_init: bool, // Set while parsing if/while conditions so `while x { }` isn't read as a struct initializer
+    no_bit_or: bool, // Set while parsing the defaults of a |x = 1| lambda so the closing | isn't read as bitwise or
+    colons_owed: usize, // ':'s an enclosing ternary or slice is waiting for. See ternary_follows
 }
 
 /*
@@ -30,6 +36,9 @@ impl Parser {
             inloop: false,
             function_stack: vec![],
             s_id : 0,
+            no_struct_init: false,
+            no_bit_or: false,
+            colons_owed: 0,
         }
     }
 
@@ -52,9 +61,17 @@ impl Parser {
             if self.function_stack.is_empty(){
                 return Err("Error Return statement only alowed in functions".to_string());
             }
+            let keyword = self.previous();
             let expr = self.expression();
             self.consume(TokenType::Semicolon)?;
-            return Ok(Statement::Return(expr));
+            return Ok(Statement::Return(keyword, expr));
+        }
+
+        if self.matcher(TokenType::Throw) {
+            let keyword = self.previous();
+            let expr = self.expression();
+            self.consume(TokenType::Semicolon)?;
+            return Ok(Statement::Throw(keyword, expr));
         }
 
         return self.expression_statement();
@@ -80,13 +97,38 @@ impl Parser {
         if self.matcher(TokenType::Let) {
             return self.declare_var();
         }
-        if self.matcher(TokenType::Fun) {
+        if self.matcher(TokenType::Const) {
+            return self.declare_const();
+        }
+        // fun (x) {} without a name is a lambda expression
+        if self.check(TokenType::Fun) && self.peek_next().token_type != TokenType::LeftParen {
+            self.advance();
             let f = self.declare_fun();
             return f;
         }
         if self.matcher(TokenType::Struct){
             return self.declare_struct();
         }
+        if self.matcher(TokenType::Enum) {
+            return self.declare_enum();
+        }
+        if self.matcher(TokenType::Impl) {
+            return self.declare_impl();
+        }
+        if self.matcher(TokenType::Class) {
+            return self.declare_class();
+        }
+        if self.matcher(TokenType::Import) {
+            return self.import();
+        }
+        // `from` is only special at the start of an import so it can still be used as a name
+        if self.check(TokenType::Identifier)
+            && self.peek().lex == "from"
+            && self.peek_next().token_type == TokenType::String
+        {
+            self.advance();
+            return self.import_from();
+        }
         return self.assignment();
         // return self.statement();
     }
@@ -94,13 +136,48 @@ impl Parser {
     // x=y
     fn assignment(&mut self) -> Result<Statement, String> {
         if self.peek().token_type == TokenType::Identifier
-            && self.peek_next().token_type == TokenType::Assignment
+            && (self.peek_next().token_type == TokenType::Assignment
+                || compound_operator(self.peek_next().token_type).is_some()
+                || self.increment_follows(1))
         {
             return self.assign_var();
         }
+        if self.is_pattern_assignment() {
+            return self.assign_pattern();
+        }
         return self.statement();
     }
 
+    // (a, b) = ... // [a, b] = ... Looks for an = right after the closing bracket
+    fn is_pattern_assignment(&self) -> bool {
+        if !self.check(TokenType::LeftParen) && !self.check(TokenType::LeftBracket) {
+            return false;
+        }
+        match self.closing_bracket(0) {
+            Some(i) => self.peek_ahead(i + 1) == TokenType::Assignment,
+            None => false,
+        }
+    }
+
+    // How far ahead the bracket closing the one n tokens ahead is. None if a ; comes first
+    fn closing_bracket(&self, n: usize) -> Option<usize> {
+        let mut depth = 0;
+        for (i, t) in self.tokens[self.current + n..].iter().enumerate() {
+            match t.token_type {
+                TokenType::LeftParen | TokenType::LeftBracket => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i + n);
+                    }
+                }
+                TokenType::Semicolon | TokenType::TERMINATE => return None,
+                _ => (),
+            }
+        }
+        None
+    }
+
     // 2+2
     fn expression(&mut self) -> Expression {
         return self.while_expr();
@@ -120,14 +197,43 @@ impl Parser {
     //while
     fn while_expr(&mut self) -> Expression {
         if self.matcher(TokenType::While) {
+            let outer = self.inloop;
             self.inloop = true;
-            let condition = self.expression();
+            let condition = self.condition();
             // println!("{:?}", condition);
             let body = self.expression();
             // println!("{:?}", body);
 
-            self.inloop = false;
+            self.inloop = outer;
             return Expression::WhileExpr(Box::new(condition), Box::new(body));
+        } else {
+            return self.for_expr();
+        }
+    }
+
+    //for x in y
+    fn for_expr(&mut self) -> Expression {
+        if self.matcher(TokenType::For) {
+            let name = self
+                .consume(TokenType::Identifier)
+                .expect("Expect loop variable after for");
+            self.consume(TokenType::In)
+                .expect("Expect 'in' after for loop variable");
+            let iterable = self.condition();
+            let outer = self.inloop;
+            self.inloop = true;
+            let body = self.expression();
+            self.inloop = outer;
+
+            return Expression::ForExpr(
+                Symbol {
+                    name: name.lex,
+                    s_id: self.alloc_sid(),
+                    line: name.line,
+                },
+                Box::new(iterable),
+                Box::new(body),
+            );
         } else {
             return self.loop_expr();
         }
@@ -136,9 +242,10 @@ impl Parser {
     //loop
     fn loop_expr(&mut self) -> Expression {
         if self.matcher(TokenType::Loop) {
+            let outer = self.inloop;
             self.inloop = true;
             let body = self.expression();
-            self.inloop = false;
+            self.inloop = outer;
 
             return Expression::LoopExpr(Box::new(body));
         } else {
@@ -149,7 +256,7 @@ impl Parser {
     //If
     pub fn if_expr(&mut self) -> Expression {
         if self.matcher(TokenType::If) {
-            let p = self.expression();
+            let p = self.condition();
             let then = self.expression();
             let mut else_s: Option<Expression> = None;
             if self.matcher(TokenType::Else) {
@@ -157,15 +264,135 @@ impl Parser {
             }
             return Expression::IfExpr(Box::new(p), Box::new(then), Box::new(else_s));
         } else {
+            return self.match_expr();
+        }
+    }
+
+    /*match x { 0 => "zero", 1..=9 if x > 2 => "small", _ => "big" }
+    The comma after an arm can be left out when its body is a block */
+    fn match_expr(&mut self) -> Expression {
+        if !self.matcher(TokenType::Match) {
+            return self.try_expr();
+        }
+        let keyword = self.previous();
+        let subject = self.condition();
+        self.consume(TokenType::LeftSquigly)
+            .expect("Expect '{' after match value");
+        let outer = self.no_struct_init;
+        self.no_struct_init = false;
+        let mut arms = vec![];
+        while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
+            let pattern = match self.match_pattern() {
+                Ok(pattern) => pattern,
+                Err(err) => panic!("{}", err),
+            };
+            let mut guard = None;
+            if self.matcher(TokenType::If) {
+                guard = Some(self.expression());
+            }
+            self.consume(TokenType::FatArrow)
+                .expect("Expect '=>' after match pattern");
+            let body = self.expression();
+            let is_block = matches!(body, Expression::BlockExpr(_));
+            arms.push(MatchArm { pattern, guard, body });
+            if !self.matcher(TokenType::Comma) && !is_block {
+                break;
+            }
+        }
+        self.consume(TokenType::RightSquigly)
+            .expect("Expect '}' at end of match");
+        self.no_struct_init = outer;
+        Expression::Match(keyword, Box::new(subject), arms)
+    }
+
+    /*try { ... } catch e { ... } finally { ... }
+    Either the catch or the finally can be left out but not both */
+    fn try_expr(&mut self) -> Expression {
+        if !self.matcher(TokenType::Try) {
             return self.block();
         }
+        let keyword = self.previous();
+        let body = self.try_block("try");
+        let mut catch = None;
+        if self.matcher(TokenType::Catch) {
+            let name = if self.check(TokenType::Identifier) {
+                let name = self.advance();
+                Some(Symbol {
+                    name: name.lex,
+                    s_id: self.alloc_sid(),
+                    line: name.line,
+                })
+            } else {
+                None
+            };
+            let body = self.try_block("catch");
+            catch = Some(Box::new(Catch { name, body }));
+        }
+        let mut finally = None;
+        if self.matcher(TokenType::Finally) {
+            finally = Some(Box::new(self.try_block("finally")));
+        }
+        if catch.is_none() && finally.is_none() {
+            panic!(
+                "[Parser Error] try needs a catch or a finally @Line {}",
+                keyword.line
+            );
+        }
+        Expression::Try(Box::new(body), catch, finally)
+    }
+
+    fn try_block(&mut self, after: &str) -> Expression {
+        if !self.check(TokenType::LeftSquigly) {
+            panic!(
+                "[Parser Error] Expect '{{' after {} @Line {}",
+                after,
+                self.peek().line
+            );
+        }
+        self.block()
+    }
+
+    // A pattern with alternatives "x" | "y". Every alternative has to bind the same names
+    fn match_pattern(&mut self) -> Result<Pattern, String> {
+        let first = self.pattern()?;
+        if !self.check(TokenType::Pipe) {
+            return Ok(first);
+        }
+        let line = self.peek().line;
+        let mut alternatives = vec![first];
+        while self.matcher(TokenType::Pipe) {
+            alternatives.push(self.pattern()?);
+        }
+        let names = |p: &Pattern| {
+            let mut names: Vec<String> = p.bindings().into_iter().map(|s| s.name).collect();
+            names.sort();
+            names
+        };
+        if alternatives.iter().any(|p| names(p) != names(&alternatives[0])) {
+            return Err(format!(
+                "[Parser Error] Every alternative in a pattern has to bind the same names @Line {}",
+                line
+            ));
+        }
+        Ok(Pattern::Or(alternatives))
+    }
+
+    // Condition of an if or while. Struct initializers aren't allowed directly inside it.
+    fn condition(&mut self) -> Expression {
+        let outer = self.no_struct_init;
+        self.no_struct_init = true;
+        let expr = self.expression();
+        self.no_struct_init = outer;
+        expr
     }
 
     // {}
     pub fn block(&mut self) -> Expression {
         let mut stmts: Vec<Statement> = vec![];
 
-        if self.matcher(TokenType::LeftSquigly) {
+        if !self.is_dict() && self.matcher(TokenType::LeftSquigly) {
+            let outer = self.no_struct_init;
+            self.no_struct_init = false;
             while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
                 match self.declaration() {
                     Ok(stmt) => stmts.push(stmt),
@@ -174,6 +401,7 @@ impl Parser {
             }
             self.consume(TokenType::RightSquigly)
                 .expect("Error ending block, } missing");
+            self.no_struct_init = outer;
             return Expression::BlockExpr(stmts);
         } else {
             return self.ternary();
@@ -183,7 +411,9 @@ impl Parser {
     fn ternary(&mut self) -> Expression {
         let ident: Expression = self.or();
         if self.matcher(TokenType::Ternary) {
+            self.colons_owed += 1;
             let r0 = self.expression();
+            self.colons_owed -= 1;
             let _ = self.consume(TokenType::Colon);
             let r1 = self.expression();
             return Expression::Ternary(Box::new(ident), Box::new(r0), Box::new(r1));
@@ -214,32 +444,93 @@ impl Parser {
 
     // x == y
     fn equality(&mut self) -> Expression {
-        let mut expr: Expression = self.comparison();
+        let mut expr: Expression = self.bit_or();
         while self.matcher(TokenType::NotEqual) || self.matcher(TokenType::Equality)
         /*|| self.matcher(TokenType::Assignment)*/
         {
             let operator: Token = self.previous();
-            let right: Expression = self.comparison();
+            let right: Expression = self.bit_or();
             expr = Expression::Binary(Box::new(expr), operator, Box::new(right))
         }
         return expr;
     }
-    // x>y
+
+    /*x | y. The bitwise operators bind tighter than == so flags & MASK == 0 checks the masked bits.
+    From loosest to tightest it goes | ^ & then comparisons and then << >> */
+    fn bit_or(&mut self) -> Expression {
+        let mut expr = self.bit_xor();
+        while !self.no_bit_or && self.matcher(TokenType::Pipe) {
+            let operator = self.previous();
+            let right = self.bit_xor();
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        expr
+    }
+
+    // x ^ y
+    fn bit_xor(&mut self) -> Expression {
+        let mut expr = self.bit_and();
+        while self.matcher(TokenType::Caret) {
+            let operator = self.previous();
+            let right = self.bit_and();
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        expr
+    }
+
+    // x & y
+    fn bit_and(&mut self) -> Expression {
+        let mut expr = self.comparison();
+        while self.matcher(TokenType::Ampersand) {
+            let operator = self.previous();
+            let right = self.comparison();
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        expr
+    }
+
+    // x>y // x in xs
     fn comparison(&mut self) -> Expression {
-        let mut expr: Expression = self.binary();
+        let mut expr: Expression = self.range();
         // println!("Token # {}" , self.current);
         while self.matcher(TokenType::Greater)
             || self.matcher(TokenType::GreaterEqual)
             || self.matcher(TokenType::Less)
             || self.matcher(TokenType::LessEqual)
+            || self.matcher(TokenType::In)
         {
             let operator: Token = self.previous();
-            let right: Expression = self.binary();
+            let right: Expression = self.range();
             expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
         }
         return expr;
     }
 
+    // 0..10 // 0..=n + 1. Ranges dont chain so 0..5..10 is an error
+    fn range(&mut self) -> Expression {
+        let expr = self.shift();
+        if self.matcher(TokenType::DotDot) || self.matcher(TokenType::DotDotEqual) {
+            let operator = self.previous();
+            let right = self.shift();
+            if self.check(TokenType::DotDot) || self.check(TokenType::DotDotEqual) {
+                panic!("[Parser Error] Ranges cant be chained @Line {}", operator.line);
+            }
+            return Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        expr
+    }
+
+    // x << 2 // x >> 2. Looser than + so 1 << n - 1 is 1 << (n - 1)
+    fn shift(&mut self) -> Expression {
+        let mut expr = self.binary();
+        while self.matcher(TokenType::LessLess) || self.matcher(TokenType::GreaterGreater) {
+            let operator = self.previous();
+            let right = self.binary();
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        expr
+    }
+
     // See if next token equals t, if so return true and pop to next token.
     fn matcher(&mut self, t: TokenType) -> bool {
         if self.check(t) {
@@ -285,6 +576,85 @@ impl Parser {
         return self.tokens.get(self.current + 1).unwrap().clone();
     }
 
+    // Get the token n places ahead of the current one. Returns TERMINATE when past the end
+    fn peek_ahead(&self, n: usize) -> TokenType {
+        match self.tokens.get(self.current + n) {
+            Some(t) => t.token_type,
+            None => TokenType::TERMINATE,
+        }
+    }
+
+    /*{:} is an empty dictionary. Otherwise the braces are a dictionary when they start with an expression
+    followed by ':' at the same nesting level. {k: 1} // {-1: "a"} // {"a{x}": 1} // {(1, 2): "a"}
+    A block can only have a ':' there from a ternary, those are skipped the same way ternary_follows does */
+    fn is_dict(&self) -> bool {
+        if !self.check(TokenType::LeftSquigly) {
+            return false;
+        }
+        let first = self.peek_ahead(1);
+        if first == TokenType::Colon {
+            return self.peek_ahead(2) == TokenType::RightSquigly;
+        }
+        // Statements and lambdas can't be keys. The ':' in { |x: int| x } belongs to the parameter
+        if !starts_expression(first)
+            || matches!(
+                first,
+                TokenType::Pipe
+                    | TokenType::Fun
+                    | TokenType::If
+                    | TokenType::Match
+                    | TokenType::Try
+                    | TokenType::While
+                    | TokenType::Loop
+                    | TokenType::For
+                    | TokenType::Break
+                    | TokenType::Continue
+            )
+        {
+            return false;
+        }
+        let mut depth = 0;
+        let mut owed = 0;
+        for (i, token) in self.tokens.iter().enumerate().skip(self.current + 1) {
+            match token.token_type {
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftSquigly => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightSquigly if depth > 0 => {
+                    depth -= 1
+                }
+                TokenType::Semicolon | TokenType::TERMINATE => return false,
+                TokenType::RightParen
+                | TokenType::RightBracket
+                | TokenType::RightSquigly
+                | TokenType::Comma
+                | TokenType::Assignment
+                    if depth == 0 =>
+                {
+                    return false
+                }
+                TokenType::Ternary if depth == 0 && starts_expression(self.peek_ahead(i + 1 - self.current)) => {
+                    owed += 1
+                }
+                TokenType::Colon if depth == 0 && owed == 0 => return true,
+                TokenType::Colon if depth == 0 => owed -= 1,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    // Point { ... } is only a struct initializer if the braces are empty or start with `field:`
+    // Anything else is a block following an identifier
+    fn is_struct_init(&self) -> bool {
+        if self.no_struct_init || !self.check(TokenType::LeftSquigly) {
+            return false;
+        }
+        match self.peek_ahead(1) {
+            TokenType::RightSquigly => true,
+            TokenType::Identifier => self.peek_ahead(2) == TokenType::Colon,
+            _ => false,
+        }
+    }
+
     // This function only exists for clarity
     // self.term and self.factor are both binary expressions they are seperated for purposes of the order of operations
     // This function just makes the order a little more clear
@@ -296,7 +666,10 @@ impl Parser {
     fn term(&mut self) -> Expression {
         let mut expr: Expression = self.factor();
 
-        while self.matcher(TokenType::Minus) || self.matcher(TokenType::Plus) {
+        // Stops before the ++ of xs[0]++; so the statement can pick it up
+        while !self.increment_follows(0)
+            && (self.matcher(TokenType::Minus) || self.matcher(TokenType::Plus))
+        {
             let operator = self.previous();
             let right = self.factor();
             expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
@@ -304,27 +677,46 @@ impl Parser {
         return expr;
     }
 
-    // 8*8  // 64/8
+    // 8*8  // 64/8 // 7%2 // 7//2
     fn factor(&mut self) -> Expression {
         let mut expr: Expression = self.unary();
-        while self.matcher(TokenType::Slash) || self.matcher(TokenType::Aster) {
+        while self.matcher(TokenType::Slash)
+            || self.matcher(TokenType::Aster)
+            || self.matcher(TokenType::Percent)
+            || self.matcher(TokenType::SlashSlash)
+        {
             let operator = self.previous();
             let right = self.unary();
             expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
         }
         return expr;
     }
-    // !true // -x
+    // !true // -x // ~x
     fn unary(&mut self) -> Expression {
-        if self.matcher(TokenType::Not) || self.matcher(TokenType::Minus) {
+        if self.matcher(TokenType::Not)
+            || self.matcher(TokenType::Minus)
+            || self.matcher(TokenType::Tilde)
+        {
             let operator = self.previous();
             let right = self.unary();
             return Expression::Unary(operator, Box::new(right));
         } else {
-            return self.call();
+            return self.power();
         }
     }
 
+    /*2**3 Binds tighter than unary minus so -2**2 is -4.
+    The right side goes back through unary which makes it right associative, 2**3**2 is 2**9 */
+    fn power(&mut self) -> Expression {
+        let expr = self.call();
+        if self.matcher(TokenType::AsterAster) {
+            let operator = self.previous();
+            let right = self.unary();
+            return Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+        expr
+    }
+
     //Function calling
     fn call(&mut self) -> Expression {
         let mut expr = self.primary();
@@ -332,6 +724,16 @@ impl Parser {
             if self.matcher(TokenType::LeftParen) {
                 expr = self.finish_call(expr);
                 // println!("{:?}", expr);
+            } else if self.matcher(TokenType::Dot) {
+                let name = self
+                    .consume(TokenType::Identifier)
+                    .expect("Expect field name after '.'");
+                expr = Expression::Get(Box::new(expr), name);
+            } else if self.matcher(TokenType::LeftBracket) {
+                expr = self.finish_index(expr);
+            } else if self.check(TokenType::Ternary) && !self.ternary_follows() {
+                let question = self.advance();
+                expr = Expression::Propagate(question, Box::new(expr));
             } else {
                 break;
             }
@@ -340,12 +742,166 @@ impl Parser {
         expr
     }
 
+    /*Whether the ? at the current token starts a ternary rather than being f()?. c ? 1 : 2 // f()?.x
+    After a -, (, [ or | it could be either, f()? - 1 // c ? -1 : 1, so it's only a ternary when a ':' follows
+    at the same nesting level that isn't already taken by a ternary or slice around it */
+    fn ternary_follows(&self) -> bool {
+        let next = self.peek_next().token_type;
+        if !starts_expression(next) {
+            return false;
+        }
+        if !matches!(
+            next,
+            TokenType::Minus | TokenType::LeftParen | TokenType::LeftBracket | TokenType::Pipe
+        ) {
+            return true;
+        }
+        let mut depth = 0;
+        let mut owed = self.colons_owed;
+        for (i, token) in self.tokens.iter().enumerate().skip(self.current + 1) {
+            match token.token_type {
+                TokenType::LeftParen | TokenType::LeftBracket => depth += 1,
+                TokenType::LeftSquigly if depth > 0 => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightSquigly if depth > 0 => {
+                    depth -= 1
+                }
+                TokenType::RightParen
+                | TokenType::RightBracket
+                | TokenType::RightSquigly
+                | TokenType::LeftSquigly
+                | TokenType::Semicolon
+                | TokenType::Comma
+                | TokenType::TERMINATE => return false,
+                // A ternary further along takes the next ':' for itself
+                TokenType::Ternary if depth == 0 && starts_expression(self.peek_ahead(i + 1 - self.current)) => {
+                    owed += 1
+                }
+                TokenType::Colon if depth == 0 && owed == 0 => return true,
+                TokenType::Colon if depth == 0 => owed -= 1,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    // x[i] // x[a:b] // x[:b] // x[a:]
+    fn finish_index(&mut self, object: Expression) -> Expression {
+        let bracket = self.previous();
+        let outer = (self.no_struct_init, self.colons_owed);
+        self.no_struct_init = false;
+        let mut start = None;
+        if !self.check(TokenType::Colon) {
+            // The first ':' is the slice's
+            self.colons_owed = 1;
+            start = Some(Box::new(self.expression()));
+            self.colons_owed = 0;
+        }
+        let expr = if self.matcher(TokenType::Colon) {
+            let mut end = None;
+            if !self.check(TokenType::RightBracket) {
+                end = Some(Box::new(self.expression()));
+            }
+            Expression::Slice(Box::new(object), bracket, start, end)
+        } else {
+            Expression::Index(
+                Box::new(object),
+                bracket,
+                start.expect("Expect index inside '[ ]'"),
+            )
+        };
+        (self.no_struct_init, self.colons_owed) = outer;
+        self.consume(TokenType::RightBracket)
+            .expect("Expect ']' after index");
+        expr
+    }
+
+    // "a {b} c {d} e" Text parts and expressions take turns until the String token ending it
+    fn interpolation(&mut self) -> Expression {
+        let outer = self.no_struct_init;
+        self.no_struct_init = false;
+        let mut parts = vec![Expression::Literal(self.previous().literal.unwrap())];
+        loop {
+            parts.push(self.expression());
+            if self.matcher(TokenType::Interpolation) {
+                parts.push(Expression::Literal(self.previous().literal.unwrap()));
+                continue;
+            }
+            let end = self
+                .consume(TokenType::String)
+                .expect("Expect '}' after interpolated expression");
+            parts.push(Expression::Literal(end.literal.unwrap()));
+            break;
+        }
+        self.no_struct_init = outer;
+        Expression::Interpolation(parts)
+    }
+
+    // {"a": 1, "b": 2}
+    fn dict(&mut self) -> Expression {
+        self.consume(TokenType::LeftSquigly)
+            .expect("Expect '{' to start dictionary");
+        let outer = self.no_struct_init;
+        self.no_struct_init = false;
+        let mut entries: Vec<(Expression, Expression)> = vec![];
+        if !self.matcher(TokenType::Colon) {
+            while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
+                let key = self.expression();
+                self.consume(TokenType::Colon)
+                    .expect("Expect ':' after dictionary key");
+                entries.push((key, self.expression()));
+                if !self.matcher(TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+        self.no_struct_init = outer;
+        self.consume(TokenType::RightSquigly)
+            .expect("Expect '}' at end of dictionary");
+        Expression::Dict(entries)
+    }
+
+    // Point { x: 1, y: 2 }
+    fn struct_init(&mut self, name: Symbol) -> Expression {
+        self.consume(TokenType::LeftSquigly)
+            .expect("Expect '{' to start struct initializer");
+        let mut fields: Vec<(Token, Expression)> = vec![];
+        while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
+            let field = self
+                .consume(TokenType::Identifier)
+                .expect("Expect field name in struct initializer");
+            self.consume(TokenType::Colon)
+                .expect("Expect ':' after field name in struct initializer");
+            fields.push((field, self.expression()));
+            if !self.matcher(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightSquigly)
+            .expect("Expect '}' at end of struct initializer");
+        Expression::StructInit(name, fields)
+    }
+
     fn finish_call(&mut self, callee: Expression) -> Expression {
         let mut args: Vec<Expression> = vec![];
+        let mut named: Vec<(Token, Expression)> = vec![];
         if !self.check(TokenType::RightParen) {
             loop {
-                let a = self.expression();
-                args.push(a);
+                // f(a, b: 2) Keyword arguments come after all the positional ones
+                if self.check(TokenType::Identifier) && self.peek_next().token_type == TokenType::Colon {
+                    let name = self.advance();
+                    self.advance();
+                    named.push((name, self.expression()));
+                } else if let Some((name, _)) = named.last() {
+                    panic!(
+                        "[Parser Error] Positional argument after keyword argument {} @Line {}",
+                        name.lex, name.line
+                    );
+                } else if self.matcher(TokenType::DotDot) {
+                    args.push(Expression::Spread(Box::new(self.expression())));
+                } else {
+                    let a = self.expression();
+                    args.push(a);
+                }
                 if !self.matcher(TokenType::Comma) {
                     break;
                 }
@@ -354,7 +910,7 @@ impl Parser {
         let token = self
             .consume(TokenType::RightParen)
             .expect("Error: ')' expected at end of args.");
-        return Expression::Call(Box::new(callee), token, args);
+        return Expression::Call(Box::new(callee), token, args, named);
     }
 
     // Bottom of tree all literals, parenthesis and identifiers.
@@ -372,16 +928,97 @@ impl Parser {
         if self.matcher(TokenType::Number) || self.matcher(TokenType::String) {
             return Expression::Literal(self.previous().literal.unwrap());
         }
+        if self.matcher(TokenType::LeftBracket) {
+            let outer = self.no_struct_init;
+            self.no_struct_init = false;
+            let mut items: Vec<Expression> = vec![];
+            while !self.check(TokenType::RightBracket) && !self.end_of_file() {
+                items.push(self.expression());
+                if !self.matcher(TokenType::Comma) {
+                    break;
+                }
+            }
+            self.no_struct_init = outer;
+            self.consume(TokenType::RightBracket)
+                .expect("Expect ']' at end of list.");
+            return Expression::List(items);
+        }
+        if self.is_dict() {
+            return self.dict();
+        }
+        if self.matcher(TokenType::Fun) {
+            self.consume(TokenType::LeftParen)
+                .expect("Expect '(' or a name after fun");
+            return self.lambda(TokenType::RightParen);
+        }
+        if self.matcher(TokenType::Pipe) {
+            return self.lambda(TokenType::Pipe);
+        }
+        if self.matcher(TokenType::Interpolation) {
+            return self.interpolation();
+        }
         if self.matcher(TokenType::LeftParen) {
+            let outer = self.no_struct_init;
+            self.no_struct_init = false;
+            // () is the empty tuple
+            if self.matcher(TokenType::RightParen) {
+                self.no_struct_init = outer;
+                return Expression::Tuple(vec![]);
+            }
             let expr = self.expression();
+            // (a, b) is a tuple. (a,) is a tuple with one value
+            if self.matcher(TokenType::Comma) {
+                let mut items = vec![expr];
+                while !self.check(TokenType::RightParen) && !self.end_of_file() {
+                    items.push(self.expression());
+                    if !self.matcher(TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.no_struct_init = outer;
+                self.consume(TokenType::RightParen)
+                    .expect("Expect ')' at end of tuple.");
+                return Expression::Tuple(items);
+            }
+            self.no_struct_init = outer;
             self.consume(TokenType::RightParen)
                 .expect("Expect ')' after expression.");
             return Expression::Grouping(Box::new(expr));
         }
         if self.matcher(TokenType::Identifier) {
-            return Expression::Primary(Symbol {
-                name: self.previous().lex,
+            let name = self.previous();
+            let sym = Symbol {
+                name: name.lex,
+                s_id: self.alloc_sid(),
+                line: name.line,
+            };
+            if self.is_struct_init() {
+                return self.struct_init(sym);
+            }
+            return Expression::Primary(sym);
+        }
+        if self.matcher(TokenType::Super) {
+            let keyword = self.previous();
+            self.consume(TokenType::Dot)
+                .expect("Expect '.' after super");
+            let method = self
+                .consume(TokenType::Identifier)
+                .expect("Expect superclass method name after 'super.'");
+            return Expression::Super(
+                Symbol {
+                    name: keyword.lex,
+                    s_id: self.alloc_sid(),
+                    line: keyword.line,
+                },
+                method,
+            );
+        }
+        if self.matcher(TokenType::This) {
+            let keyword = self.previous();
+            return Expression::This(Symbol {
+                name: keyword.lex,
                 s_id: self.alloc_sid(),
+                line: keyword.line,
             });
         }
         if self.matcher(TokenType::Break) {
@@ -453,10 +1090,17 @@ impl Parser {
     // Statment for expression
     fn expression_statement(&mut self) -> Result<Statement, String> {
         let ex = self.expression();
+        if self.matcher(TokenType::Assignment) {
+            return self.assign_target(ex);
+        }
+        if compound_operator(self.peek().token_type).is_some() || self.increment_follows(0) {
+            return self.compound_target(ex);
+        }
         match ex {
             Expression::BlockExpr(_) => (),
             Expression::IfExpr(_, _, _) => (),
             Expression::WhileExpr(_, _) => (),
+            Expression::ForExpr(_, _, _) => (),
             _ => (),
             // _ => _ = self.consume(TokenType::Semicolon)?,
         }
@@ -470,9 +1114,9 @@ impl Parser {
 
     //Statment used for variable declaration
     fn declare_var(&mut self) -> Result<Statement, String> {
-        let name: Token = self
-            .consume(TokenType::Identifier)
-            .expect("Expect identifier after let");
+        let line = self.peek().line;
+        let pattern = self.pattern()?;
+        let ty = self.annotation()?;
 
         let mut init: Option<Expression> = None;
         if self.matcher(TokenType::Assignment) {
@@ -480,19 +1124,225 @@ impl Parser {
             init = Some(ex);
         }
 
+        if init.is_none() && !matches!(pattern, Pattern::Binding(_)) {
+            return Err(format!(
+                "[Parser Error] Destructuring let needs a value @Line {}",
+                line
+            ));
+        }
+
         self.consume(TokenType::Semicolon)
             .expect("Expect ; after variable declaration");
 
-        return Ok(Statement::Declaration(Symbol { name: name.lex, s_id: self.alloc_sid()}, init));
+        return Ok(Statement::Declaration(pattern, ty, init));
+    }
+
+    // const x = 2; // const (a, b) = (1, 2); Unlike let there always has to be a value
+    fn declare_const(&mut self) -> Result<Statement, String> {
+        let line = self.peek().line;
+        let pattern = self.pattern()?;
+        let ty = self.annotation()?;
+        if !self.matcher(TokenType::Assignment) {
+            return Err(format!("[Parser Error] const needs a value @Line {}", line));
+        }
+        let init = self.expression();
+        self.consume(TokenType::Semicolon)
+            .expect("Expect ; after const declaration");
+        Ok(Statement::ConstDeclaration(pattern, ty, init))
+    }
+
+    // : Number after a name. Types are checked by the checker, the interpreter ignores them
+    fn annotation(&mut self) -> Result<Option<Token>, String> {
+        if !self.matcher(TokenType::Colon) {
+            return Ok(None);
+        }
+        self.type_name().map(Some)
+    }
+
+    fn type_name(&mut self) -> Result<Token, String> {
+        if self.matcher(TokenType::Nil) {
+            return Ok(self.previous());
+        }
+        match self.consume(TokenType::Identifier) {
+            Ok(t) => Ok(t),
+            Err(_) => Err(format!(
+                "[Parser Error] Expect a type name @Line {}",
+                self.peek().line
+            )),
+        }
+    }
+
+    // x // _ // (a, b) // [first, ..rest]
+    fn pattern(&mut self) -> Result<Pattern, String> {
+        if self.matcher(TokenType::LeftParen) {
+            let mut items = self.pattern_items(TokenType::RightParen)?;
+            // (a) is just a, a tuple with one value is written (a,)
+            let trailing_comma = self.tokens[self.current - 2].token_type == TokenType::Comma;
+            if items.len() == 1 && !trailing_comma && !matches!(items[0], Pattern::Rest(_)) {
+                return Ok(items.remove(0));
+            }
+            return Ok(Pattern::Tuple(items));
+        }
+        if self.matcher(TokenType::LeftBracket) {
+            return Ok(Pattern::List(self.pattern_items(TokenType::RightBracket)?));
+        }
+        if self.matcher(TokenType::DotDot) {
+            let line = self.previous().line;
+            return Err(format!(
+                "[Parser Error] '..' only allowed inside tuple and list patterns @Line {}",
+                line
+            ));
+        }
+        // 0 // -1 // 1..10 // 1..=9
+        if self.check(TokenType::Number) || self.check(TokenType::Minus) {
+            let start = self.pattern_number()?;
+            if self.matcher(TokenType::DotDot) || self.matcher(TokenType::DotDotEqual) {
+                let inclusive = self.previous().token_type == TokenType::DotDotEqual;
+                let end = self.pattern_number()?;
+                let line = self.previous().line;
+                return Ok(Pattern::Range(
+                    range_bound(start, line)?,
+                    range_bound(end, line)?,
+                    inclusive,
+                ));
+            }
+            return Ok(Pattern::Literal(start));
+        }
+        if self.matcher(TokenType::String) {
+            return Ok(Pattern::Literal(self.previous().literal.unwrap()));
+        }
+        if self.matcher(TokenType::True) {
+            return Ok(Pattern::Literal(Literal::True));
+        }
+        if self.matcher(TokenType::False) {
+            return Ok(Pattern::Literal(Literal::False));
+        }
+        if self.matcher(TokenType::Nil) {
+            return Ok(Pattern::Literal(Literal::Nil));
+        }
+        let name = self.consume(TokenType::Identifier)?;
+        if name.lex == "_" {
+            return Ok(Pattern::Wildcard);
+        }
+        if self.matcher(TokenType::LeftSquigly) {
+            return self.struct_pattern(name);
+        }
+        // Shape.Circle(r) // Shape.Empty
+        if self.matcher(TokenType::Dot) {
+            let variant = self.consume(TokenType::Identifier)?;
+            let mut items = None;
+            if self.matcher(TokenType::LeftParen) {
+                items = Some(self.pattern_items(TokenType::RightParen)?);
+            }
+            return Ok(Pattern::Variant(name.lex, variant.lex, items));
+        }
+        // Ok(v) // Err(e) are short for Result.Ok(v) // Result.Err(e)
+        if (name.lex == "Ok" || name.lex == "Err") && self.matcher(TokenType::LeftParen) {
+            let items = self.pattern_items(TokenType::RightParen)?;
+            return Ok(Pattern::Variant("Result".to_string(), name.lex, Some(items)));
+        }
+        Ok(Pattern::Binding(Symbol {
+            name: name.lex,
+            s_id: self.alloc_sid(),
+            line: name.line,
+        }))
+    }
+
+    fn pattern_number(&mut self) -> Result<Literal, String> {
+        let negative = self.matcher(TokenType::Minus);
+        match self.consume(TokenType::Number)?.literal {
+            Some(Literal::Int(n)) if negative => Ok(Literal::Int(-n)),
+            // -9223372036854775808 is lexed as a BigInt before it gets its minus
+            Some(Literal::BigInt(n)) if negative => match (-&n).to_i64() {
+                Some(n) => Ok(Literal::Int(n)),
+                None => Ok(Literal::BigInt(-&n)),
+            },
+            Some(Literal::Number(n)) if negative => Ok(Literal::Number(-n)),
+            Some(n @ (Literal::Int(_) | Literal::BigInt(_) | Literal::Number(_))) => Ok(n),
+            _ => unreachable!("Number token without a number literal"),
+        }
+    }
+
+    // Point { x, y: 0 } // Point { x, .. } Fields that aren't named aren't checked, the .. is only there for readers
+    fn struct_pattern(&mut self, name: Token) -> Result<Pattern, String> {
+        let mut fields = vec![];
+        while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
+            if self.matcher(TokenType::DotDot) {
+                break;
+            }
+            let field = self.consume(TokenType::Identifier)?;
+            let pattern = if self.matcher(TokenType::Colon) {
+                self.pattern()?
+            } else {
+                Pattern::Binding(Symbol {
+                    name: field.lex.clone(),
+                    s_id: self.alloc_sid(),
+                    line: field.line,
+                })
+            };
+            fields.push((field.lex, pattern));
+            if !self.matcher(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightSquigly)?;
+        Ok(Pattern::Struct(name.lex, fields))
+    }
+
+    // Patterns seperated by commas up to the closing bracket. One of them can be ..rest
+    fn pattern_items(&mut self, close: TokenType) -> Result<Vec<Pattern>, String> {
+        let mut items = vec![];
+        while !self.check(close) && !self.end_of_file() {
+            if self.matcher(TokenType::DotDot) {
+                let line = self.previous().line;
+                if items.iter().any(|p| matches!(p, Pattern::Rest(_))) {
+                    return Err(format!(
+                        "[Parser Error] Only one '..' allowed in a pattern @Line {}",
+                        line
+                    ));
+                }
+                let mut rest = None;
+                if self.check(TokenType::Identifier) {
+                    let name = self.advance();
+                    rest = Some(Symbol {
+                        name: name.lex,
+                        s_id: self.alloc_sid(),
+                        line: name.line,
+                    });
+                }
+                items.push(Pattern::Rest(rest));
+            } else {
+                items.push(self.pattern()?);
+            }
+            if !self.matcher(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(close)?;
+        Ok(items)
+    }
+
+    //Statment used for destructuring assignment (a, b) = (b, a);
+    fn assign_pattern(&mut self) -> Result<Statement, String> {
+        let pattern = self.pattern()?;
+        self.consume(TokenType::Assignment)?;
+        let expr = self.expression();
+        self.consume(TokenType::Semicolon)?;
+        Ok(Statement::AssignPattern(pattern, expr))
     }
     //Statment used for variable assignment
     fn assign_var(&mut self) -> Result<Statement, String> {
         let name: Token = self
             .consume(TokenType::Identifier)
             .expect("Error on parsing assignment");
-        self.consume(TokenType::Assignment)
-            .expect("Expect = for assignment expression");
-        let expr = self.expression();
+        // x += 1 keeps the operator as a + token so the interpreter can treat it like x + 1
+        let (operator, expr) = if self.check(TokenType::Assignment) {
+            self.advance();
+            (None, self.expression())
+        } else {
+            let (token, expr) = self.compound_operand();
+            (Some(token), expr)
+        };
         match expr {
             Expression::BlockExpr(_) => (),
             Expression::IfExpr(_, _, _) => (),
@@ -504,45 +1354,310 @@ impl Parser {
             }
         }
 
-        return Ok(Statement::Assignment(Symbol { name: name.lex , s_id: self.alloc_sid()}, expr));
+        let sym = Symbol { name: name.lex, s_id: self.alloc_sid(), line: name.line };
+        match operator {
+            Some(op) => Ok(Statement::CompoundAssignment(sym, op, expr)),
+            None => Ok(Statement::Assignment(sym, expr)),
+        }
+    }
+
+    /*x.a += y // x[0] += y. Like x += y the operator is kept as a + token. The interpreter works out the
+    object and index once so xs[f()] += 1 only calls f once */
+    fn compound_target(&mut self, target: Expression) -> Result<Statement, String> {
+        let (operator, expr) = self.compound_operand();
+        self.consume(TokenType::Semicolon)?;
+        match target {
+            Expression::Get(_, _) | Expression::Index(_, _, _) => {
+                Ok(Statement::CompoundSet(target, operator, expr))
+            }
+            _ => Err(format!(
+                "[Parser Error] Invalid assignment target for {} @Line {}",
+                operator.lex, operator.line
+            )),
+        }
+    }
+
+    // The operator of a compound assignment turned into its arithmetic one and the value it is used with
+    fn compound_operand(&mut self) -> (Token, Expression) {
+        let mut operator = self.advance();
+        if let Some(op) = compound_operator(operator.token_type) {
+            operator.token_type = op;
+            return (operator, self.expression());
+        }
+        // x++ is x += 1, the first + is already the operator
+        let second = self.advance();
+        operator.lex.push_str(&second.lex);
+        (operator, Expression::Literal(Literal::Int(1)))
+    }
+
+    /*x++ // x-- Only as a statement, they have no value and there is no ++x. Neither is valid otherwise
+    since there is no unary + and x - - is missing its operand, so the ; tells them apart from x - -1 */
+    fn increment_follows(&self, n: usize) -> bool {
+        let op = self.peek_ahead(n);
+        matches!(op, TokenType::Plus | TokenType::Minus)
+            && self.peek_ahead(n + 1) == op
+            && self.peek_ahead(n + 2) == TokenType::Semicolon
+    }
+
+    //Statment used for field and index assignment x.a = y // x[0] = y
+    fn assign_target(&mut self, target: Expression) -> Result<Statement, String> {
+        let equals = self.previous();
+        let expr = self.expression();
+        match expr {
+            Expression::BlockExpr(_) => (),
+            Expression::IfExpr(_, _, _) => (),
+            Expression::WhileExpr(_, _) => (),
+            _ => {
+                _ = self
+                    .consume(TokenType::Semicolon)
+                    .expect("Expect ; after assignment")
+            }
+        }
+        match target {
+            Expression::Get(object, name) => Ok(Statement::SetField(*object, name, expr)),
+            Expression::Index(object, bracket, index) => {
+                Ok(Statement::SetIndex(*object, bracket, *index, expr))
+            }
+            _ => Err(format!(
+                "[Parser Error] Invalid assignment target @Line {}",
+                equals.line
+            )),
+        }
     }
 
     fn declare_fun(&mut self) -> Result<Statement, String> {
         let name = self.consume(TokenType::Identifier)?;
         _ = self.consume(TokenType::LeftParen)?;
-        let mut params: Vec<Symbol> = vec![];
         self.function_stack.push(0);
-        if !self.check(TokenType::RightParen) {
-            loop {
-                params.push(Symbol {
-                    name: self.consume(TokenType::Identifier)?.lex,
-                    s_id: self.alloc_sid(),
-                });
-                if !self.matcher(TokenType::Comma) {
-                    break;
-                }
-            }
-        }
+        let params = self.parameters(TokenType::RightParen)?;
         self.consume(TokenType::RightParen)?;
+        let ret = if self.matcher(TokenType::Arrow) {
+            Some(self.type_name()?)
+        } else {
+            None
+        };
 
         let body = self.block();
         self.function_stack.pop();
         return Ok(Statement::FuncDclaration(
-            Symbol { name: name.lex, s_id: self.alloc_sid() },
+            Symbol { name: name.lex, s_id: self.alloc_sid(), line: name.line },
             params,
+            ret,
             body,
         ));
     }
 
+    /*a, b = 1, ..rest up to the closing token. Once a parameter has a default value all the ones after it need one too.
+    The rest parameter has to be last */
+    fn parameters(&mut self, close: TokenType) -> Result<Vec<Param>, String> {
+        let mut params: Vec<Param> = vec![];
+        if self.check(close) {
+            return Ok(params);
+        }
+        loop {
+            let rest = self.matcher(TokenType::DotDot);
+            let name = self.consume(TokenType::Identifier)?;
+            if let Some(last) = params.last().filter(|p| p.rest) {
+                return Err(format!(
+                    "[Parser Error] Rest parameter {} has to be the last parameter @Line {}",
+                    last.name.name, name.line
+                ));
+            }
+            let ty = self.annotation()?;
+            let default = if !rest && self.matcher(TokenType::Assignment) {
+                let outer = self.no_bit_or;
+                self.no_bit_or = close == TokenType::Pipe;
+                let default = self.expression();
+                self.no_bit_or = outer;
+                Some(default)
+            } else {
+                None
+            };
+            if default.is_none() && !rest && params.last().is_some_and(|p| p.default.is_some()) {
+                return Err(format!(
+                    "[Parser Error] Parameter {} needs a default value because the one before it has one @Line {}",
+                    name.lex, name.line
+                ));
+            }
+            params.push(Param {
+                name: Symbol { name: name.lex, s_id: self.alloc_sid(), line: name.line },
+                default,
+                rest,
+                ty,
+            });
+            if !self.matcher(TokenType::Comma) {
+                break;
+            }
+        }
+        Ok(params)
+    }
+
+    /*fun (x, y) { x + y } // |x, y| x + y
+    The body of the short form is any expression, it's wrapped in a block so both forms become normal functions */
+    fn lambda(&mut self, close: TokenType) -> Expression {
+        let line = self.previous().line;
+        let params = match self.parameters(close) {
+            Ok(params) => params,
+            Err(err) => panic!("{}", err),
+        };
+        self.consume(close).expect("Expect end of lambda parameters");
+
+        let outer = (self.inloop, self.no_struct_init, self.no_bit_or);
+        self.inloop = false;
+        self.no_struct_init = false;
+        self.no_bit_or = false;
+        self.function_stack.push(0);
+        let body = if close == TokenType::Pipe {
+            let expr = self.expression();
+            Expression::BlockExpr(vec![Statement::Expression(expr)])
+        } else if self.check(TokenType::LeftSquigly) {
+            self.block()
+        } else {
+            panic!("[Parser Error] Expect '{{' before lambda body @Line {}", self.peek().line)
+        };
+        self.function_stack.pop();
+        (self.inloop, self.no_struct_init, self.no_bit_or) = outer;
+
+        let name = Symbol { name: format!("lambda@{}", line), s_id: self.alloc_sid(), line };
+        Expression::Lambda(name, params, Box::new(body))
+    }
+
     fn declare_struct(&mut self) -> Result<Statement, String>{
         let name = self.consume(TokenType::Identifier)?;
         _ = self.consume(TokenType::LeftSquigly)?;
-        let mut field : Vec<Symbol> = vec![];
+        let mut fields : Vec<(Symbol, Option<Token>)> = vec![];
+        let mut methods: Vec<Statement> = vec![];
         while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
-            // This is where fields will be added
+            if self.matcher(TokenType::Fun) {
+                methods.push(self.declare_fun()?);
+                continue;
+            }
+            let field = self.consume(TokenType::Identifier)?;
+            if fields.iter().any(|(f, _)| f.name == field.lex) {
+                return Err(format!(
+                    "[Parser Error] Field {} declared twice in struct {} @Line {}",
+                    field.lex, name.lex, field.line
+                ));
+            }
+            let ty = self.annotation()?;
+            fields.push((Symbol { name: field.lex, s_id: self.alloc_sid(), line: field.line }, ty));
+            if !self.matcher(TokenType::Comma) && !self.check(TokenType::Fun) {
+                break;
+            }
         }
-        self.consume(TokenType::RightSquigly);
-        return Ok(Statement::StructDeclaration(Symbol {name:name.lex, s_id: self.alloc_sid()}));
+        self.consume(TokenType::RightSquigly)?;
+        return Ok(Statement::StructDeclaration(Symbol { name: name.lex, s_id: self.alloc_sid(), line: name.line }, fields, methods));
+    }
+
+    // enum x { A(a), B(a, b), C }
+    fn declare_enum(&mut self) -> Result<Statement, String> {
+        let name = self.consume(TokenType::Identifier)?;
+        self.consume(TokenType::LeftSquigly)?;
+        let mut variants: Vec<(Token, Vec<Symbol>)> = vec![];
+        while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
+            let variant = self.consume(TokenType::Identifier)?;
+            if variants.iter().any(|(v, _)| v.lex == variant.lex) {
+                return Err(format!(
+                    "[Parser Error] Variant {} declared twice in enum {} @Line {}",
+                    variant.lex, name.lex, variant.line
+                ));
+            }
+            let mut fields = vec![];
+            if self.matcher(TokenType::LeftParen) {
+                while !self.check(TokenType::RightParen) && !self.end_of_file() {
+                    let field = self.consume(TokenType::Identifier)?;
+                    fields.push(Symbol { name: field.lex, s_id: self.alloc_sid(), line: field.line });
+                    if !self.matcher(TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.consume(TokenType::RightParen)?;
+            }
+            variants.push((variant, fields));
+            if !self.matcher(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightSquigly)?;
+        Ok(Statement::EnumDeclaration(
+            Symbol { name: name.lex, s_id: self.alloc_sid(), line: name.line },
+            variants,
+        ))
+    }
+
+    // import "util/math.scrlt" as m; Without the `as` the module is named after the file
+    fn import(&mut self) -> Result<Statement, String> {
+        let path = self.consume(TokenType::String)?;
+        let name = if self.check(TokenType::Identifier) && self.peek().lex == "as" {
+            self.advance();
+            self.consume(TokenType::Identifier)?.lex
+        } else {
+            module::default_name(&import_path(&path))
+        };
+        self.consume(TokenType::Semicolon)?;
+        let sym = Symbol { name, s_id: self.alloc_sid(), line: path.line };
+        Ok(Statement::Import(
+            path,
+            sym,
+            None,
+        ))
+    }
+
+    // from "util/math.scrlt" import a, b;
+    fn import_from(&mut self) -> Result<Statement, String> {
+        let path = self.consume(TokenType::String)?;
+        self.consume(TokenType::Import)?;
+        let mut names = vec![];
+        loop {
+            let name = self.consume(TokenType::Identifier)?;
+            names.push(Symbol { name: name.lex, s_id: self.alloc_sid(), line: name.line });
+            if !self.matcher(TokenType::Comma) {
+                break;
+            }
+        }
+        self.consume(TokenType::Semicolon)?;
+        let name = module::default_name(&import_path(&path));
+        let sym = Symbol { name, s_id: self.alloc_sid(), line: path.line };
+        Ok(Statement::Import(
+            path,
+            sym,
+            Some(names),
+        ))
+    }
+
+    // impl x { fun y() {} }
+    fn declare_impl(&mut self) -> Result<Statement, String> {
+        let name = self.consume(TokenType::Identifier)?;
+        self.consume(TokenType::LeftSquigly)?;
+        let mut methods: Vec<Statement> = vec![];
+        while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
+            self.consume(TokenType::Fun)?;
+            methods.push(self.declare_fun()?);
+        }
+        self.consume(TokenType::RightSquigly)?;
+        return Ok(Statement::ImplDeclaration(Symbol { name: name.lex, s_id: self.alloc_sid(), line: name.line }, methods));
+    }
+
+    // class x < y { fun z() {} }
+    fn declare_class(&mut self) -> Result<Statement, String> {
+        let name = self.consume(TokenType::Identifier)?;
+        let mut superclass = None;
+        if self.matcher(TokenType::Less) {
+            let sup = self.consume(TokenType::Identifier)?;
+            superclass = Some(Symbol { name: sup.lex, s_id: self.alloc_sid(), line: sup.line });
+        }
+        self.consume(TokenType::LeftSquigly)?;
+        let mut methods: Vec<Statement> = vec![];
+        while !self.check(TokenType::RightSquigly) && !self.end_of_file() {
+            self.consume(TokenType::Fun)?;
+            methods.push(self.declare_fun()?);
+        }
+        self.consume(TokenType::RightSquigly)?;
+        return Ok(Statement::ClassDeclaration(
+            Symbol { name: name.lex, s_id: self.alloc_sid(), line: name.line },
+            superclass,
+            methods,
+        ));
     }
 
     fn alloc_sid(&mut self) -> u64 {
@@ -567,4 +1682,68 @@ impl Parser {
 //     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 //         writeln!(f, "[Parsing Error] @ Line {}: {}", self.parser.tokens[self.parser.current].line, self.error_msg)
 //     }
-// }
\ No newline at end of file
+// }
+
+// The text of the string token after import or from
+pub fn import_path(path: &Token) -> String {
+    match &path.literal {
+        Some(Literal::Str(s)) => s.clone(),
+        _ => path.lex.clone(),
+    }
+}
+
+// The operator behind a compound assignment, += is +
+fn compound_operator(t: TokenType) -> Option<TokenType> {
+    match t {
+        TokenType::PlusEqual => Some(TokenType::Plus),
+        TokenType::MinusEqual => Some(TokenType::Minus),
+        TokenType::AsterEqual => Some(TokenType::Aster),
+        TokenType::SlashEqual => Some(TokenType::Slash),
+        TokenType::PercentEqual => Some(TokenType::Percent),
+        _ => None,
+    }
+}
+
+// Tokens an expression can begin with. Used to tell f()? apart from x ? a : b
+fn starts_expression(t: TokenType) -> bool {
+    matches!(
+        t,
+        TokenType::Identifier
+            | TokenType::Number
+            | TokenType::String
+            | TokenType::Interpolation
+            | TokenType::True
+            | TokenType::False
+            | TokenType::Nil
+            | TokenType::This
+            | TokenType::Super
+            | TokenType::LeftParen
+            | TokenType::LeftBracket
+            | TokenType::LeftSquigly
+            | TokenType::Minus
+            | TokenType::Not
+            | TokenType::Tilde
+            | TokenType::Pipe
+            | TokenType::Fun
+            | TokenType::If
+            | TokenType::Match
+            | TokenType::Try
+            | TokenType::While
+            | TokenType::Loop
+            | TokenType::For
+            | TokenType::Break
+            | TokenType::Continue
+    )
+}
+
+// Range patterns only hold ints, 1.5..2 is an error like it is for range values
+fn range_bound(lit: Literal, line: usize) -> Result<BigInt, String> {
+    match lit {
+        Literal::Int(n) => Ok(BigInt::from(n)),
+        Literal::BigInt(n) => Ok(n),
+        _ => Err(format!(
+            "[Parser Error] Range pattern bounds must be ints @Line {}",
+            line
+        )),
+    }
+}
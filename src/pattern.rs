@@ -0,0 +1,223 @@
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
+
+use crate::{
+    bigint::BigInt, error::ErrorValue, expression::Symbol, interpreter::Value, token::Literal,
+};
+
+/*Left hand side of a let or assignment and the arms of a match. let (q, r) = ... // let [first, ..rest] = ...
+Literals, ranges and struct patterns can fail to match, which is only useful in a match */
+#[derive(Clone)]
+pub enum Pattern {
+    Binding(Symbol),                               // x
+    Wildcard,                                      // _
+    Rest(Option<Symbol>), // ..rest // .. Only allowed inside tuple and list patterns
+    Tuple(Vec<Pattern>),  // (a, b)
+    List(Vec<Pattern>),   // [a, b]
+    Literal(Literal),     // 0 // "x" // true // nil
+    Range(BigInt, BigInt, bool), // 1..10 // 1..=9 The bool is true when the end is included
+    Struct(String, Vec<(String, Pattern)>), // Point { x, y: 0 } // x is short for x: x
+    Or(Vec<Pattern>),     // "x" | "y"
+    Variant(String, String, Option<Vec<Pattern>>), // Shape.Circle(r) // Shape.Empty Without ( ) the values aren't checked
+}
+
+impl Pattern {
+    // Every name the pattern binds, in order. Every alternative of an or pattern binds the same names
+    pub fn bindings(&self) -> Vec<Symbol> {
+        match self {
+            Pattern::Binding(sym) => vec![sym.clone()],
+            Pattern::Rest(Some(sym)) => vec![sym.clone()],
+            Pattern::Rest(None) | Pattern::Wildcard => vec![],
+            Pattern::Literal(_) | Pattern::Range(..) => vec![],
+            Pattern::Tuple(items) | Pattern::List(items) => {
+                items.iter().flat_map(|p| p.bindings()).collect()
+            }
+            Pattern::Struct(_, fields) => fields.iter().flat_map(|(_, p)| p.bindings()).collect(),
+            Pattern::Or(alternatives) => alternatives[0].bindings(),
+            Pattern::Variant(_, _, Some(items)) => {
+                items.iter().flat_map(|p| p.bindings()).collect()
+            }
+            Pattern::Variant(_, _, None) => vec![],
+        }
+    }
+
+    // Where the first name it binds was written, 0 when it binds none
+    pub fn line(&self) -> usize {
+        self.bindings().first().map_or(0, |sym| sym.line)
+    }
+
+    // Matches anything without binding it to a name that could be used in a guard
+    pub fn is_catch_all(&self) -> bool {
+        matches!(self, Pattern::Wildcard | Pattern::Binding(_))
+    }
+
+    // Pairs each name in the pattern with the part of val it stands for. Err when val doesn't fit the pattern
+    pub fn destructure(&self, val: Value) -> Result<Vec<(Symbol, Value)>, ErrorValue> {
+        match (self, val) {
+            (Pattern::Binding(sym), val) => Ok(vec![(sym.clone(), val)]),
+            (Pattern::Wildcard, _) => Ok(vec![]),
+            (Pattern::Tuple(items), Value::Tuple(t)) => {
+                destructure_seq(items, t.to_vec(), |rest| Value::Tuple(Rc::new(rest)))
+            }
+            (Pattern::List(items), Value::List(l)) => {
+                let values = l.borrow().clone();
+                destructure_seq(items, values, |rest| {
+                    Value::List(Rc::new(RefCell::new(rest)))
+                })
+            }
+            (Pattern::Literal(lit), v) => {
+                let expected = literal_value(lit);
+                if v == expected {
+                    Ok(vec![])
+                } else {
+                    Err(mismatch(format!("{} doesn't match {}", v, expected)))
+                }
+            }
+            // Matches the same numbers `in` does on a range value. 2.0 is 2 but 2.5 is in no range
+            (
+                Pattern::Range(start, end, inclusive),
+                v @ (Value::Int(_) | Value::BigInt(_) | Value::Number(_)),
+            ) => {
+                let n = match &v {
+                    Value::Number(n) => BigInt::from_f64(*n),
+                    v => v.as_bigint(),
+                };
+                let Some(n) = n else {
+                    return Err(mismatch(format!("{} is not an int", v)));
+                };
+                let in_range = n >= *start && if *inclusive { n <= *end } else { n < *end };
+                if in_range {
+                    Ok(vec![])
+                } else {
+                    Err(mismatch(format!("{} is outside of the range", v)))
+                }
+            }
+            (Pattern::Struct(name, fields), Value::Struct(s)) => {
+                let instance = s.borrow();
+                if !instance.def.is_a(name) {
+                    return Err(mismatch(format!("{} is not a {}", instance.def.name, name)));
+                }
+                let mut bound = vec![];
+                for (field, p) in fields {
+                    bound.extend(p.destructure(instance.get(field)?)?);
+                }
+                Ok(bound)
+            }
+            (Pattern::Variant(def, name, items), Value::Variant(v)) => {
+                if v.def.name != *def || v.name() != name {
+                    return Err(mismatch(format!("{} is not {}.{}", v, def, name)));
+                }
+                match items {
+                    Some(items) => {
+                        destructure_seq(items, v.values.clone(), |rest| Value::Tuple(Rc::new(rest)))
+                    }
+                    None => Ok(vec![]),
+                }
+            }
+            (Pattern::Or(alternatives), v) => {
+                for p in alternatives {
+                    if let Ok(bound) = p.destructure(v.clone()) {
+                        return Ok(bound);
+                    }
+                }
+                Err(mismatch(format!(
+                    "{} doesn't match any of the alternatives",
+                    v
+                )))
+            }
+            (Pattern::Tuple(_), v) => Err(mismatch(format!("Cant destructure {} as a tuple", v))),
+            (Pattern::List(_), v) => Err(mismatch(format!("Cant destructure {} as a list", v))),
+            (Pattern::Range(..), v) => Err(mismatch(format!("{} is not an int", v))),
+            (Pattern::Struct(name, _), v) => Err(mismatch(format!("{} is not a {}", v, name))),
+            (Pattern::Variant(def, name, _), v) => {
+                Err(mismatch(format!("{} is not {}.{}", v, def, name)))
+            }
+            (Pattern::Rest(_), _) => Err(mismatch(
+                "'..' can only be used inside tuple and list patterns".to_string(),
+            )),
+        }
+    }
+}
+
+fn mismatch(message: String) -> ErrorValue {
+    ErrorValue::new("MatchError", message, 0)
+}
+
+fn literal_value(lit: &Literal) -> Value {
+    match lit {
+        Literal::Str(s) => Value::String(s.clone()),
+        Literal::Int(n) => Value::Int(*n),
+        Literal::BigInt(n) => Value::BigInt(Rc::new(n.clone())),
+        Literal::Number(n) => Value::Number(*n),
+        Literal::True => Value::Bool(true),
+        Literal::False => Value::Bool(false),
+        Literal::Nil => Value::Nil,
+    }
+}
+
+/*Patterns before the rest match the front of the sequence and the ones after it match the back.
+Whatever is left over in the middle is handed to the rest binding as a new tuple or list */
+fn destructure_seq(
+    items: &[Pattern],
+    mut values: Vec<Value>,
+    collect_rest: fn(Vec<Value>) -> Value,
+) -> Result<Vec<(Symbol, Value)>, ErrorValue> {
+    let rest = items.iter().position(|p| matches!(p, Pattern::Rest(_)));
+    let fixed = items.len() - rest.map_or(0, |_| 1);
+    if values.len() < fixed || (rest.is_none() && values.len() != fixed) {
+        return Err(mismatch(format!(
+            "Pattern expects {}{} values but got {}",
+            if rest.is_some() { "at least " } else { "" },
+            fixed,
+            values.len()
+        )));
+    }
+    let mut bound = vec![];
+    match rest {
+        Some(i) => {
+            let after = values.split_off(values.len() - (items.len() - i - 1));
+            let middle = values.split_off(i);
+            for (p, v) in items[..i].iter().zip(values) {
+                bound.extend(p.destructure(v)?);
+            }
+            if let Pattern::Rest(Some(sym)) = &items[i] {
+                bound.push((sym.clone(), collect_rest(middle)));
+            }
+            for (p, v) in items[i + 1..].iter().zip(after) {
+                bound.extend(p.destructure(v)?);
+            }
+        }
+        None => {
+            for (p, v) in items.iter().zip(values) {
+                bound.extend(p.destructure(v)?);
+            }
+        }
+    }
+    Ok(bound)
+}
+
+impl Debug for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Binding(arg0) => f.debug_tuple("Binding").field(arg0).finish(),
+            Self::Wildcard => write!(f, "Wildcard"),
+            Self::Rest(arg0) => f.debug_tuple("Rest").field(arg0).finish(),
+            Self::Tuple(arg0) => f.debug_tuple("Tuple").field(arg0).finish(),
+            Self::List(arg0) => f.debug_tuple("List").field(arg0).finish(),
+            Self::Literal(arg0) => f.debug_tuple("Literal").field(arg0).finish(),
+            Self::Range(arg0, arg1, arg2) => f
+                .debug_tuple("Range")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::Struct(arg0, arg1) => f.debug_tuple("Struct").field(arg0).field(arg1).finish(),
+            Self::Or(arg0) => f.debug_tuple("Or").field(arg0).finish(),
+            Self::Variant(arg0, arg1, arg2) => f
+                .debug_tuple("Variant")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+        }
+    }
+}
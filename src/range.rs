@@ -0,0 +1,119 @@
+use std::fmt::{Debug, Display};
+
+use crate::error::ErrorValue;
+
+/* Ints from start up to end, stepping by step. 0..10 leaves out the end and 0..=10 includes it.
+Ranges are values so iterating one works on a copy and never builds a list */
+#[derive(Clone, Copy, PartialEq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub step: i64, // Never 0. Negative steps count down from start
+    pub inclusive: bool,
+}
+
+impl Range {
+    pub fn new(start: i64, end: i64) -> Range {
+        Range {
+            start,
+            end,
+            step: 1,
+            inclusive: false,
+        }
+    }
+
+    pub fn inclusive(start: i64, end: i64) -> Range {
+        Range {
+            inclusive: true,
+            ..Range::new(start, end)
+        }
+    }
+
+    // (0..10).step(2) is 0, 2, 4, 6, 8
+    pub fn step(self, step: i64) -> Result<Range, ErrorValue> {
+        if step == 0 {
+            return Err(ErrorValue::new("ValueError", "Range step cant be 0", 0));
+        }
+        Ok(Range { step, ..self })
+    }
+
+    // Whether n hasn't gone past the end yet
+    fn before_end(&self, n: i64) -> bool {
+        match (self.step > 0, self.inclusive) {
+            (true, true) => n <= self.end,
+            (true, false) => n < self.end,
+            (false, true) => n >= self.end,
+            (false, false) => n > self.end,
+        }
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        let past_start = match self.step > 0 {
+            true => n >= self.start,
+            false => n <= self.start,
+        };
+        // i128 so the distance between two far apart i64s cant overflow
+        past_start
+            && self.before_end(n)
+            && (n as i128 - self.start as i128) % self.step as i128 == 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.before_end(self.start)
+    }
+
+    // u128 since i64::MIN..=i64::MAX has one more item than a u64 can count
+    pub fn len(&self) -> u128 {
+        if self.is_empty() {
+            return 0;
+        }
+        let span = (self.end as i128 - self.start as i128).abs();
+        let step = (self.step as i128).abs();
+        let count = match self.inclusive {
+            true => span / step + 1,
+            false => (span + step - 1) / step,
+        };
+        count as u128
+    }
+}
+
+impl Iterator for Range {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        if self.is_empty() {
+            return None;
+        }
+        let n = self.start;
+        match n.checked_add(self.step) {
+            Some(next) => self.start = next,
+            // Stepping past i64::MAX or i64::MIN means n was the last one
+            None => {
+                self.end = n;
+                self.inclusive = false;
+            }
+        }
+        Some(n)
+    }
+}
+
+impl Debug for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Range")
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("step", &self.step)
+            .field("inclusive", &self.inclusive)
+            .finish()
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dots = if self.inclusive { "..=" } else { ".." };
+        match self.step {
+            1 => write!(f, "{}{}{}", self.start, dots, self.end),
+            step => write!(f, "({}{}{}).step({})", self.start, dots, self.end, step),
+        }
+    }
+}
@@ -1,15 +1,44 @@
-use std::{collections::HashMap, vec};
+use std::{collections::{HashMap, HashSet}, path::PathBuf, rc::Rc, vec};
 
-use crate::{expression::{Expression, Symbol}, statement::Statement};
+use crate::{expression::{Catch, Expression, MatchArm, Param, Symbol}, lexer::Lexer, module::{self, Module}, parser::{self, Parser}, pattern::Pattern, statement::Statement, token::Token};
+
+// What kind of declaration the resolver is currently inside of. Used to check `this` and `super`
+#[derive(Clone, Copy, PartialEq)]
+enum ClassKind{
+    None,
+    Struct,
+    Class,
+    Subclass,
+}
 
 pub struct Resolver{
     scope_stack : Vec<HashMap<String, bool>>,
+    constants : Vec<HashSet<String>>, // Names declared with const in each scope of the scope stack
     pub lex_scope : HashMap<u64, usize>,
+    current_class : ClassKind,
+    in_function : bool, // ? returns from the function it is in so it can't be used outside of one
+    file : PathBuf, // The file being resolved. Its imports are looked up next to it
+    next_sid : u64, // Symbol ids of imported files start after the ones already handed out
+    loading : Vec<PathBuf>, // Files being resolved right now. Importing one of them again is a cycle
+    modules : HashMap<PathBuf, Rc<Module>>,
+    pub imports : HashMap<u64, Rc<Module>>, // The module each import statement brings in, by the id of its symbol
 }
 
 impl Resolver{
-    pub fn new() -> Resolver{
-        Resolver{scope_stack: vec![], lex_scope : HashMap::new()}
+    pub fn new(file : PathBuf, next_sid : u64) -> Resolver{
+        let file = file.canonicalize().unwrap_or(file);
+        Resolver{
+            scope_stack: vec![],
+            constants: vec![],
+            lex_scope : HashMap::new(),
+            current_class : ClassKind::None,
+            in_function : false,
+            file : file.clone(),
+            next_sid,
+            loading : vec![file],
+            modules : HashMap::new(),
+            imports : HashMap::new(),
+        }
     }
 
     pub fn block_expr(&mut self, block : crate::expression::Expression){
@@ -20,25 +49,184 @@ impl Resolver{
         self.end_scope();
     }
 
-    pub fn decl_stmt(&mut self, sym : Symbol, init : Option<Expression>){
-        self.declare(sym.name.clone());
+    // Each name can only be bound once in a pattern. let (a, a) = (1, 2); would define a twice
+    fn unique_bindings(&self, pattern : &Pattern) -> Vec<Symbol>{
+        let names = pattern.bindings();
+        for (i, sym) in names.iter().enumerate() {
+            if names[..i].iter().any(|s| s.name == sym.name) {
+                panic!("[Resolve Error] {} is bound more than once in the same pattern @Line {}", sym.name, sym.line);
+            }
+        }
+        names
+    }
+
+    pub fn decl_stmt(&mut self, pattern : Pattern, init : Option<Expression>){
+        let names = self.unique_bindings(&pattern);
+        for sym in &names {
+            self.declare(sym.name.clone());
+        }
         if let Some(expr) = init {
             self.resolve_expr(expr);
         }
+        for sym in names {
+            self.define(sym.name);
+        }
+    }
+
+    pub fn const_stmt(&mut self, pattern : Pattern, init : Expression){
+        let names = pattern.bindings();
+        self.decl_stmt(pattern, Some(init));
+        if let Some(constants) = self.constants.last_mut() {
+            constants.extend(names.into_iter().map(|sym| sym.name));
+        }
+    }
+
+    pub fn struct_stmt(&mut self, sym : Symbol, methods : Vec<Statement>){
+        self.declare(sym.name.clone());
         self.define(sym.name.clone());
+        self.methods(methods, ClassKind::Struct);
+    }
+
+    pub fn impl_stmt(&mut self, sym : Symbol, methods : Vec<Statement>){
+        self.resolve_local(sym);
+        self.methods(methods, ClassKind::Struct);
     }
 
-    pub fn struct_stmt(&mut self, sym : Symbol){
+    // Subclasses have an extra scope around their methods holding `super`
+    pub fn class_stmt(&mut self, sym : Symbol, superclass : Option<Symbol>, methods : Vec<Statement>){
         self.declare(sym.name.clone());
         self.define(sym.name.clone());
+        match superclass {
+            Some(sup) => {
+                if sup.name == sym.name {
+                    panic!("[Resolve Error] Class {} cant inherit from itself", sym.name);
+                }
+                self.resolve_local(sup);
+                self.begin_scope();
+                self.define("super".to_string());
+                self.methods(methods, ClassKind::Subclass);
+                self.end_scope();
+            }
+            None => self.methods(methods, ClassKind::Class),
+        }
+    }
+
+    // Each method gets a scope holding `this` around the scope for its parameters
+    fn methods(&mut self, methods : Vec<Statement>, kind : ClassKind){
+        let enclosing_class = self.current_class;
+        self.current_class = kind;
+        for method in methods{
+            if let Statement::FuncDclaration(_, params, _, body) = method{
+                self.begin_scope();
+                self.define("this".to_string());
+                self.function_body(params, body);
+                self.end_scope();
+            }
+        }
+        self.current_class = enclosing_class;
+    }
+
+    /*Imported files are resolved on their own, as if they were the file being run. Only the names declared
+    at their top can be imported. from "x" import a; fails here when x has no a */
+    fn import_stmt(&mut self, path : Token, sym : Symbol, names : Option<Vec<Symbol>>){
+        let file = match module::find(&parser::import_path(&path), &self.file) {
+            Ok(file) => file,
+            Err(e) => panic!("[Resolve Error] {} @Line {}", e.message, path.line),
+        };
+        if let Some(i) = self.loading.iter().position(|f| *f == file) {
+            let cycle: Vec<String> = self.loading[i..].iter().chain([&file]).map(|f| f.display().to_string()).collect();
+            panic!("[Resolve Error] Import cycle {} @Line {}", cycle.join(" -> "), path.line);
+        }
+        let module = match self.modules.get(&file) {
+            Some(module) => module.clone(),
+            None => self.load_module(file),
+        };
+        match names {
+            Some(names) => {
+                for name in names {
+                    if !module.names.contains(&name.name) {
+                        panic!("[Resolve Error] Module {} has no {} to import @Line {}", module.name, name.name, path.line);
+                    }
+                    self.declare(name.name.clone());
+                    if module.constants.contains(&name.name) {
+                        if let Some(constants) = self.constants.last_mut() {
+                            constants.insert(name.name.clone());
+                        }
+                    }
+                    self.define(name.name);
+                }
+            }
+            None => {
+                self.declare(sym.name.clone());
+                self.define(sym.name.clone());
+            }
+        }
+        self.imports.insert(sym.s_id, module);
+    }
+
+    fn load_module(&mut self, file : PathBuf) -> Rc<Module>{
+        let src = match std::fs::read_to_string(&file) {
+            Ok(src) => src,
+            Err(e) => panic!("[Resolve Error] Couldn't read {}: {}", file.display(), e),
+        };
+        let mut lexer = Lexer::new(&src);
+        lexer.scan_tokens();
+        let mut parser = Parser::new(lexer.tokens);
+        parser.s_id = self.next_sid;
+        let stmts = parser.parse().expect("Parsing failure");
+        self.next_sid = parser.s_id;
+
+        let scope_stack = std::mem::take(&mut self.scope_stack);
+        let constants = std::mem::take(&mut self.constants);
+        let enclosing_class = std::mem::replace(&mut self.current_class, ClassKind::None);
+        let enclosing_function = std::mem::replace(&mut self.in_function, false);
+        let importer = std::mem::replace(&mut self.file, file.clone());
+        self.loading.push(file.clone());
+
+        self.begin_scope();
+        self.resolve_stmts(stmts.clone());
+        let names = self.scope_stack.last().map(|s| s.keys().cloned().collect()).unwrap_or_default();
+        let module_constants = self.constants.last().cloned().unwrap_or_default();
+        self.end_scope();
+
+        self.loading.pop();
+        self.file = importer;
+        self.in_function = enclosing_function;
+        self.current_class = enclosing_class;
+        self.scope_stack = scope_stack;
+        self.constants = constants;
+
+        let module = Rc::new(Module {
+            name: module::default_name(&file.to_string_lossy()),
+            path: file.clone(),
+            stmts,
+            names,
+            constants: module_constants,
+        });
+        self.modules.insert(file, module.clone());
+        module
     }
 
     fn begin_scope(&mut self) {
         self.scope_stack.push(HashMap::new());
+        self.constants.push(HashSet::new());
     }
 
     fn end_scope(&mut self) {
         self.scope_stack.pop();
+        self.constants.pop();
+    }
+
+    // The closest scope declaring the name decides if it is a constant
+    fn check_assignable(&self, sym: &Symbol) {
+        for (scope, constants) in self.scope_stack.iter().zip(&self.constants).rev() {
+            if scope.contains_key(&sym.name) {
+                if constants.contains(&sym.name) {
+                    panic!("[Resolve Error] Cant assign to {}. It was declared with const @Line {}", sym.name, sym.line);
+                }
+                return;
+            }
+        }
     }
 
     pub fn resolve(&mut self, stmts: Vec<Statement>){
@@ -58,30 +246,75 @@ impl Resolver{
     fn resolve_stmt(&mut self, stmt: crate::statement::Statement) {
         match stmt {
             crate::statement::Statement::Expression(expr) => self.resolve_expr(expr.clone()),
-            crate::statement::Statement::Declaration(sym, init) => self.decl_stmt(sym, init),
+            crate::statement::Statement::Declaration(sym, _, init) => self.decl_stmt(sym, init),
+            crate::statement::Statement::ConstDeclaration(pattern, _, init) => self.const_stmt(pattern, init),
             crate::statement::Statement::Assignment(sym, expr) => self.assign_stmt(sym, expr),
-            crate::statement::Statement::FuncDclaration(name, params, expr) => self.function_declaration(name,params,expr),
-            crate::statement::Statement::Return(expr) => self.return_stmt(expr),
-            crate::statement::Statement::StructDeclaration(name) => self.struct_stmt(name),
+            crate::statement::Statement::CompoundAssignment(sym, _, expr) => self.assign_stmt(sym, expr),
+            crate::statement::Statement::AssignPattern(pattern, expr) => self.assign_pattern(pattern, expr),
+            crate::statement::Statement::FuncDclaration(name, params, _, expr) => self.function_declaration(name,params,expr),
+            crate::statement::Statement::Return(_, expr) => self.return_stmt(expr),
+            crate::statement::Statement::Throw(_, expr) => self.resolve_expr(expr),
+            crate::statement::Statement::Import(path, sym, names) => self.import_stmt(path, sym, names),
+            crate::statement::Statement::StructDeclaration(name, _, methods) => self.struct_stmt(name, methods),
+            crate::statement::Statement::EnumDeclaration(name, _) => {
+                self.declare(name.name.clone());
+                self.define(name.name);
+            }
+            crate::statement::Statement::ImplDeclaration(name, methods) => self.impl_stmt(name, methods),
+            crate::statement::Statement::ClassDeclaration(name, superclass, methods) => self.class_stmt(name, superclass, methods),
+            crate::statement::Statement::SetField(object, _, expr) => self.set_field(object, expr),
+            crate::statement::Statement::SetIndex(object, _, index, expr) => self.set_index(object, index, expr),
+            crate::statement::Statement::CompoundSet(target, _, expr) => {
+                self.resolve_expr(expr);
+                self.resolve_expr(target);
+            }
         }
     }
 
     fn resolve_expr(&mut self, expr: Expression) {
         match expr {
-            Expression::Binary(left, _, right) => self.binary(left,right),
-            Expression::Logical(left, _, right) => self.logical(left,right),
-            Expression::Unary(op, expr) => self.unary(expr),
+            Expression::Binary(left, _, right) => self.binary(*left, *right),
+            Expression::Logical(left, _, right) => self.logical(*left, *right),
+            Expression::Unary(_, expr) => self.unary(*expr),
             Expression::Literal(_) => self.literal(),
-            Expression::Grouping(expr) => self.grouping(expr),
+            Expression::Grouping(expr) => self.grouping(*expr),
             Expression::Ternary(condition, then, elses) => self.ternary(*condition,*then,*elses),
             Expression::Primary(sym) => self.var_expr(sym),
-            Expression::Call(callee, _, args) => self.call(callee,args),
+            Expression::Call(callee, _, args, named) => self.call(*callee, args, named),
             Expression::BlockExpr(_) => self.block_expr(expr),
-            Expression::IfExpr(condition, then, _else) => self.if_expr(condition,then,_else),
+            Expression::IfExpr(condition, then, _else) => self.if_expr(*condition, *then, *_else),
             Expression::LoopExpr(body) => self.loop_expr(*body),
-            Expression::WhileExpr(condition, body) => self.while_expr(condition,body),
+            Expression::WhileExpr(condition, body) => self.while_expr(*condition, *body),
+            Expression::ForExpr(var, iterable, body) => self.for_expr(var, *iterable, *body),
             Expression::BreakExpr => {},
             Expression::ContinueExpr => {},
+            Expression::Get(object, _) => self.resolve_expr(*object),
+            Expression::StructInit(name, fields) => self.struct_init(name, fields),
+            Expression::This(sym) => self.this_expr(sym),
+            Expression::Lambda(_, params, body) => self.function_body(params, *body),
+            Expression::Spread(items) => self.resolve_expr(*items),
+            Expression::Match(keyword, subject, arms) => self.match_expr(keyword, *subject, arms),
+            Expression::Try(body, catch, finally) => self.try_expr(*body, catch, finally),
+            Expression::Propagate(question, expr) => {
+                if !self.in_function {
+                    panic!("[Resolve Error] ? can only be used inside a function @Line {}", question.line);
+                }
+                self.resolve_expr(*expr);
+            }
+            Expression::List(items) | Expression::Tuple(items) | Expression::Interpolation(items) => {
+                for item in items {
+                    self.resolve_expr(item);
+                }
+            }
+            Expression::Dict(entries) => {
+                for (key, val) in entries {
+                    self.resolve_expr(key);
+                    self.resolve_expr(val);
+                }
+            }
+            Expression::Index(object, _, index) => self.binary(*object, *index),
+            Expression::Slice(object, _, start, end) => self.slice(*object, start, end),
+            Expression::Super(sym, _) => self.super_expr(sym),
         }
     }
 
@@ -120,12 +353,40 @@ impl Resolver{
 
     fn assign_stmt(&mut self, sym: Symbol, expr: Expression) {
         self.resolve_expr(expr);
+        self.check_assignable(&sym);
+        self.resolve_local(sym);
+    }
+
+    fn this_expr(&mut self, sym: Symbol) {
+        if self.current_class == ClassKind::None {
+            panic!("[Resolve Error] Cant use 'this' outside of a method");
+        }
         self.resolve_local(sym);
     }
 
-    fn function_declaration(&mut self, name: Symbol, params: Vec<Symbol>, expr: Expression) {
+    fn super_expr(&mut self, sym: Symbol) {
+        match self.current_class {
+            ClassKind::Subclass => self.resolve_local(sym),
+            ClassKind::None => panic!("[Resolve Error] Cant use 'super' outside of a class"),
+            _ => panic!("[Resolve Error] Cant use 'super' in a class with no superclass"),
+        }
+    }
+
+    fn assign_pattern(&mut self, pattern: Pattern, expr: Expression) {
+        self.resolve_expr(expr);
+        for sym in pattern.bindings() {
+            self.check_assignable(&sym);
+            self.resolve_local(sym);
+        }
+    }
+
+    fn function_declaration(&mut self, name: Symbol, params: Vec<Param>, expr: Expression) {
         self.declare(name.name.clone());
         self.define(name.name.clone());
+        self.function_body(params, expr);
+    }
+
+    fn function_body(&mut self, params: Vec<Param>, expr: Expression) {
         let stmts;
         if let Expression::BlockExpr(b_stmts) = expr {
             stmts = b_stmts;
@@ -133,19 +394,26 @@ impl Resolver{
             panic!("[Resolve Error] Function Declratation");
         }
 
+        let enclosing_function = self.in_function;
+        self.in_function = true;
         self.begin_scope();
+        // Defaults are resolved before their own parameter is declared so they can only see the ones before it
         for param in params{
-            self.declare(param.name.clone());
-            self.define(param.name.clone());
+            if let Some(default) = param.default {
+                self.resolve_expr(default);
+            }
+            self.declare(param.name.name.clone());
+            self.define(param.name.name.clone());
         }
         self.resolve_stmts(stmts);
         self.end_scope();
+        self.in_function = enclosing_function;
     }
 
-    fn if_expr(&mut self, condition: Box<Expression>, then: Box<Expression>, _else: Box<Option<Expression>>) {
-        self.resolve_expr(*condition);
-        self.resolve_expr(*then);
-        if let Some(els) = *_else{
+    fn if_expr(&mut self, condition: Expression, then: Expression, _else: Option<Expression>) {
+        self.resolve_expr(condition);
+        self.resolve_expr(then);
+        if let Some(els) = _else{
             self.resolve_expr(els);
         }
     }
@@ -154,38 +422,88 @@ impl Resolver{
         self.resolve_expr(expr);
     }
 
-    fn while_expr(&mut self, condition: Box<Expression>, body: Box<Expression>) {
-        self.resolve_expr(*condition);
-        self.resolve_expr(*body);
+    fn while_expr(&mut self, condition: Expression, body: Expression) {
+        self.resolve_expr(condition);
+        self.resolve_expr(body);
+    }
+
+    // The loop variable lives in its own scope around the body
+    fn for_expr(&mut self, var: Symbol, iterable: Expression, body: Expression) {
+        self.resolve_expr(iterable);
+        self.begin_scope();
+        self.define(var.name);
+        self.resolve_expr(body);
+        self.end_scope();
+    }
+
+    // Each arm gets its own scope with the names its pattern binds, the guard can use them too
+    fn match_expr(&mut self, keyword: Token, subject: Expression, arms: Vec<MatchArm>) {
+        self.resolve_expr(subject);
+        let catch_all = arms.last().is_some_and(|arm| arm.pattern.is_catch_all() && arm.guard.is_none());
+        if !catch_all {
+            eprintln!("[Resolve Warning] match has no trailing _ arm and will error on values no arm matches @Line {}", keyword.line);
+        }
+        for arm in arms {
+            self.begin_scope();
+            for sym in self.unique_bindings(&arm.pattern) {
+                self.declare(sym.name.clone());
+                self.define(sym.name);
+            }
+            if let Some(guard) = arm.guard {
+                self.resolve_expr(guard);
+            }
+            self.resolve_expr(arm.body);
+            self.end_scope();
+        }
+    }
+
+    // The catch gets its own scope holding the error, even when it doesn't name it
+    fn try_expr(&mut self, body: Expression, catch: Option<Box<Catch>>, finally: Option<Box<Expression>>) {
+        self.resolve_expr(body);
+        if let Some(catch) = catch {
+            self.begin_scope();
+            if let Some(name) = catch.name {
+                self.declare(name.name.clone());
+                self.define(name.name);
+            }
+            self.resolve_expr(catch.body);
+            self.end_scope();
+        }
+        if let Some(finally) = finally {
+            self.resolve_expr(*finally);
+        }
     }
 
-    fn binary(&mut self, left: Box<Expression>, right: Box<Expression>) {
-        self.resolve_expr(*left);
-        self.resolve_expr(*right);
+    fn binary(&mut self, left: Expression, right: Expression) {
+        self.resolve_expr(left);
+        self.resolve_expr(right);
     }
 
-    fn call(&mut self, callee: Box<Expression>, args: Vec<Expression>) {
-        self.resolve_expr(*callee);
+    fn call(&mut self, callee: Expression, args: Vec<Expression>, named: Vec<(Token, Expression)>) {
+        self.resolve_expr(callee);
         for expr in args{
             self.resolve_expr(expr);
         }
+        for (_, expr) in named {
+            self.resolve_expr(expr);
+        }
     }
 
-    fn grouping(&mut self, expr: Box<Expression>) {
-        self.resolve_expr(*expr);
+    fn grouping(&mut self, expr: Expression) {
+        self.resolve_expr(expr);
     }
 
     fn literal(&mut self) {
         return;
     }
 
-    fn logical(&mut self, left: Box<Expression>, right: Box<Expression>) {
-        self.resolve_expr(*left);
-        self.resolve_expr(*right);
+    fn logical(&mut self, left: Expression, right: Expression) {
+        self.resolve_expr(left);
+        self.resolve_expr(right);
     }
 
-    fn unary(&mut self, expr: Box<Expression>) {
-        self.resolve_expr(*expr);
+    fn unary(&mut self, expr: Expression) {
+        self.resolve_expr(expr);
     }
 
     fn ternary(&mut self, condition: Expression, then: Expression, elses: Expression) {
@@ -198,6 +516,34 @@ impl Resolver{
         self.resolve_expr(body);
     }
 
+    fn set_field(&mut self, object: Expression, expr: Expression) {
+        self.resolve_expr(expr);
+        self.resolve_expr(object);
+    }
+
+    fn set_index(&mut self, object: Expression, index: Expression, expr: Expression) {
+        self.resolve_expr(expr);
+        self.resolve_expr(object);
+        self.resolve_expr(index);
+    }
+
+    fn slice(&mut self, object: Expression, start: Option<Box<Expression>>, end: Option<Box<Expression>>) {
+        self.resolve_expr(object);
+        if let Some(start) = start {
+            self.resolve_expr(*start);
+        }
+        if let Some(end) = end {
+            self.resolve_expr(*end);
+        }
+    }
+
+    fn struct_init(&mut self, name: Symbol, fields: Vec<(crate::token::Token, Expression)>) {
+        self.resolve_local(name);
+        for (_, expr) in fields {
+            self.resolve_expr(expr);
+        }
+    }
+
 
 
 
@@ -1,19 +1,28 @@
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-use crate::{expression::Symbol, interpreter::Value};
+use crate::{error::ErrorValue, expression::Symbol, interpreter::Value};
 
+/*Values are shared between clones of a scope. A function's closure is a clone of the scope it was
+declared in, so any change made through the closure is seen by the declaring scope and the other way around. */
 #[derive(Clone)]
 pub struct Scope {
-    pub values: HashMap<String, Value>,
+    pub values: Rc<RefCell<HashMap<String, Value>>>,
     // pub funcs: HashMap<String, Function>,
+    // Names defined with const. The resolver catches most assignments to them, assign_at catches the rest
+    pub constants: Rc<RefCell<HashSet<String>>>,
     pub enclosing: Option<Box<Scope>>,
 }
 
 impl Scope {
     pub fn new(enclosing: Option<Box<Scope>>) -> Scope {
         Scope {
-            values: HashMap::new(),
+            values: Rc::new(RefCell::new(HashMap::new())),
             // funcs: HashMap::new(),
+            constants: Rc::new(RefCell::new(HashSet::new())),
             enclosing: enclosing,
         }
     }
@@ -23,53 +32,50 @@ impl Scope {
         if self.contains_key(&sym) {
             panic!("Cant define variable twice. [CONSIDER GETTING BETTER ERROR REPORTING THIS SHOULD BE EASY]")
         }
-        self.values.insert(sym.name, val);
+        self.values.borrow_mut().insert(sym.name, val);
+    }
+
+    pub fn define_const(&mut self, sym: Symbol, val: Value) {
+        self.constants.borrow_mut().insert(sym.name.clone());
+        self.define_var(sym, val);
     }
 
     pub fn load(&mut self, loading: HashMap<String, Value>) {
-        self.values.extend(loading);
+        self.values.borrow_mut().extend(loading);
     }
 
     pub fn contains_key(&self, key: &Symbol) -> bool {
-        if self.values.contains_key(&key.name) {
+        if self.values.borrow().contains_key(&key.name) {
             return true;
         } else {
             false
         }
     }
 
-    pub(crate) fn get_at(&self, sym: Symbol, d: usize) -> Value {
-        if let Some(val) = self.ancestor(d).values.get(&sym.name){
-
-            return val.clone();
-        }else{
-            panic!("Error getting value {} in lex scope. Expcted @ depth {}", sym.name, d);
+    // Names the resolver couldn't find are looked up in the global scope, they might not be there either
+    pub(crate) fn get_at(&self, sym: Symbol, d: usize) -> Result<Value, ErrorValue> {
+        match self.ancestor(d).values.borrow().get(&sym.name) {
+            Some(val) => Ok(val.clone()),
+            None => Err(ErrorValue::new("NameError", format!("{} is not defined", sym.name), sym.line)),
         }
     }
 
-    pub fn assign_at(&mut self, sym: Symbol, val : Value, d: usize){
-        self.ancestor_mut(d).values.insert(sym.name.clone(), val);
-    }
-
-    pub fn ancestor(&self, dist : usize) -> Scope{
-        let mut ret = self.clone();
-        for _ in 0..dist{
-            if let Some(e) = ret.enclosing.clone(){
-            ret = (*e).clone();
-            }else{
-                panic!("Enclosing scope not found");
-            }
+    pub fn assign_at(&mut self, sym: Symbol, val : Value, d: usize) -> Result<(), ErrorValue>{
+        let scope = self.ancestor(d);
+        if scope.constants.borrow().contains(&sym.name) {
+            return Err(ErrorValue::new("TypeError", format!("Cant assign to {}. It was declared with const", sym.name), sym.line));
         }
-        return ret.clone();
+        scope.values.borrow_mut().insert(sym.name.clone(), val);
+        Ok(())
     }
 
-    pub fn ancestor_mut(&mut self, dist: usize) -> &mut Scope{
+    pub fn ancestor(&self, dist : usize) -> &Scope{
         let mut ret = self;
         for _ in 0..dist{
-            if let Some(e) = &mut ret.enclosing{
-                ret = &mut (*e);
+            if let Some(e) = &ret.enclosing{
+                ret = e;
             }else{
-                panic!();
+                panic!("Enclosing scope not found");
             }
         }
         return ret;
@@ -1,6 +1,10 @@
-use crate::expression::{
-    Expression::{self},
-    Symbol,
+use crate::{
+    expression::{
+        Expression::{self},
+        Param, Symbol,
+    },
+    pattern::Pattern,
+    token::Token,
 };
 
 use std::fmt::Debug;
@@ -9,36 +13,112 @@ use std::fmt::Debug;
 pub enum Statement /*StatementType */ {
     // Print(Expression),                       // print x
     Expression(Expression),                  // 2+2 // x+y
-    Declaration(Symbol, Option<Expression>), // let x = 2
+    Declaration(Pattern, Option<Token>, Option<Expression>), // let x = 2 // let (a, b) = (1, 2) // let x: Number = 2
+    ConstDeclaration(Pattern, Option<Token>, Expression),    // const x = 2 // Can't be assigned to after
     Assignment(Symbol, Expression),          // x = 2
-    FuncDclaration(Symbol, Vec<Symbol>, Expression),
-    StructDeclaration(Symbol),
-    Return(Expression),
+    CompoundAssignment(Symbol, Token, Expression), // x += 2 // The token is the + of the +=
+    AssignPattern(Pattern, Expression),      // (a, b) = (b, a)
+    FuncDclaration(Symbol, Vec<Param>, Option<Token>, Expression), // fun f(a) -> Number {} The token is the return type
+    StructDeclaration(Symbol, Vec<(Symbol, Option<Token>)>, Vec<Statement>), // struct x { a, b: Number, fun c() {} }
+    EnumDeclaration(Symbol, Vec<(Token, Vec<Symbol>)>), // enum x { A(a), B(a, b), C }
+    ImplDeclaration(Symbol, Vec<Statement>), // impl x { fun c() {} }
+    ClassDeclaration(Symbol, Option<Symbol>, Vec<Statement>), // class x < y { fun c() {} }
+    SetField(Expression, Token, Expression), // x.a = 2
+    SetIndex(Expression, Token, Expression, Expression), // x[0] = 2
+    CompoundSet(Expression, Token, Expression), // x.a += 2 // x[0] += 2 // The target is a Get or Index
+    Return(Token, Expression),
+    Throw(Token, Expression), // throw error("x")
+    /*import "x.scrlt" as m; // from "x.scrlt" import a, b; The token is the path. The symbol is the module,
+    it is only bound when no names are listed */
+    Import(Token, Symbol, Option<Vec<Symbol>>),
 }
 
 impl Debug for Statement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Expression(arg0) => f.debug_tuple("Expression").field(arg0).finish(),
-            Self::Declaration(arg0, arg1) => f
+            Self::Declaration(arg0, arg1, arg2) => f
                 .debug_tuple("Declaration")
                 .field(arg0)
                 .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::ConstDeclaration(arg0, arg1, arg2) => f
+                .debug_tuple("ConstDeclaration")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
                 .finish(),
             Self::Assignment(arg0, arg1) => {
                 f.debug_tuple("Assignment").field(arg0).field(arg1).finish()
             }
-            Self::FuncDclaration(arg0, arg1, arg2) => f
+            Self::CompoundAssignment(arg0, arg1, arg2) => f
+                .debug_tuple("CompoundAssignment")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::AssignPattern(arg0, arg1) => f
+                .debug_tuple("AssignPattern")
+                .field(arg0)
+                .field(arg1)
+                .finish(),
+            Self::FuncDclaration(arg0, arg1, arg2, arg3) => f
                 .debug_tuple("FuncDclaration")
                 .field(arg0)
                 .field(arg1)
                 .field(arg2)
+                .field(arg3)
                 .finish(),
-            Self::StructDeclaration(arg0) => f
+            Self::StructDeclaration(arg0, arg1, arg2) => f
                 .debug_tuple("StructDeclaration")
                 .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::EnumDeclaration(arg0, arg1) => f
+                .debug_tuple("EnumDeclaration")
+                .field(arg0)
+                .field(arg1)
+                .finish(),
+            Self::ClassDeclaration(arg0, arg1, arg2) => f
+                .debug_tuple("ClassDeclaration")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::ImplDeclaration(arg0, arg1) => f
+                .debug_tuple("ImplDeclaration")
+                .field(arg0)
+                .field(arg1)
+                .finish(),
+            Self::SetIndex(arg0, arg1, arg2, arg3) => f
+                .debug_tuple("SetIndex")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .field(arg3)
+                .finish(),
+            Self::SetField(arg0, arg1, arg2) => f
+                .debug_tuple("SetField")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::CompoundSet(arg0, arg1, arg2) => f
+                .debug_tuple("CompoundSet")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::Return(arg0, arg1) => f.debug_tuple("Return").field(arg0).field(arg1).finish(),
+            Self::Throw(arg0, arg1) => f.debug_tuple("Throw").field(arg0).field(arg1).finish(),
+            Self::Import(arg0, arg1, arg2) => f
+                .debug_tuple("Import")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
                 .finish(),
-            Self::Return(arg0) => f.debug_tuple("Return").field(arg0).finish(),
         }
     }
 }
@@ -0,0 +1,135 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Debug, rc::Rc};
+
+use crate::{error::ErrorValue, interpreter::Value};
+
+/*Created by a struct or class declaration. Holds the names of the fields in the order they were declared.
+Methods map to their id in the interpreters function map, impl blocks can add more after the declaration.
+Classes don't declare their fields, any field can be set on an instance of a class. */
+#[derive(Clone)]
+pub struct StructDef {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: RefCell<HashMap<String, u64>>,
+    pub superclass: Option<Rc<StructDef>>,
+    pub is_class: bool,
+}
+
+impl StructDef {
+    pub fn new(name: String, fields: Vec<String>) -> StructDef {
+        StructDef {
+            name,
+            fields,
+            methods: RefCell::new(HashMap::new()),
+            superclass: None,
+            is_class: false,
+        }
+    }
+
+    pub fn class(name: String, superclass: Option<Rc<StructDef>>) -> StructDef {
+        StructDef {
+            superclass,
+            is_class: true,
+            ..StructDef::new(name, vec![])
+        }
+    }
+
+    // Looks for the method in this struct first then up through its superclasses
+    pub fn find_method(&self, name: &str) -> Option<u64> {
+        if let Some(f_id) = self.methods.borrow().get(name) {
+            return Some(*f_id);
+        }
+        self.superclass.as_ref()?.find_method(name)
+    }
+
+    // True when this is the struct or class called name or a subclass of it
+    pub fn is_a(&self, name: &str) -> bool {
+        self.name == name || self.superclass.as_ref().is_some_and(|s| s.is_a(name))
+    }
+
+    pub fn has_field(&self, field: &str) -> bool {
+        self.is_class || self.fields.iter().any(|f| f == field)
+    }
+}
+
+impl Debug for StructDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StructDef")
+            .field("name", &self.name)
+            .field("fields", &self.fields)
+            .field("methods", &self.methods.borrow())
+            .field("superclass", &self.superclass.as_ref().map(|s| &s.name))
+            .finish()
+    }
+}
+
+/*An instance of a struct. Instances live on the heap (Rc<RefCell<_>>) so that every variable
+holding the same instance sees changes made to its fields. Order keeps the fields in the order they are printed. */
+#[derive(Clone)]
+pub struct StructInstance {
+    pub def: Rc<StructDef>,
+    pub values: HashMap<String, Value>,
+    order: Vec<String>,
+}
+
+impl StructInstance {
+    pub fn new(def: Rc<StructDef>) -> StructInstance {
+        let values = def.fields.iter().map(|f| (f.clone(), Value::Nil)).collect();
+        let order = def.fields.clone();
+        StructInstance { def, values, order }
+    }
+
+    pub fn get(&self, field: &str) -> Result<Value, ErrorValue> {
+        match self.values.get(field) {
+            Some(v) => Ok(v.clone()),
+            None => Err(self.no_field(field)),
+        }
+    }
+
+    pub fn set(&mut self, field: &str, val: Value) -> Result<(), ErrorValue> {
+        if !self.def.has_field(field) {
+            return Err(self.no_field(field));
+        }
+        if self.values.insert(field.to_string(), val).is_none() {
+            self.order.push(field.to_string());
+        }
+        Ok(())
+    }
+
+    fn no_field(&self, field: &str) -> ErrorValue {
+        ErrorValue::new(
+            "AttributeError",
+            format!("Struct {} has no field {}", self.def.name, field),
+            0,
+        )
+    }
+
+    pub fn shared(self) -> Rc<RefCell<StructInstance>> {
+        Rc::new(RefCell::new(self))
+    }
+}
+
+impl Debug for StructInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StructInstance")
+            .field("def", &self.def.name)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for StructInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {{", self.def.name)?;
+        for (i, field) in self.order.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, " {}: {}", field, self.values[field])?;
+        }
+        if self.order.is_empty() {
+            write!(f, "}}")
+        } else {
+            write!(f, " }}")
+        }
+    }
+}
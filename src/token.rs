@@ -1,17 +1,39 @@
 use std::fmt::{self};
+
+use crate::bigint::BigInt;
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TokenType {
     Ternary, //?
     Colon,
     LeftParen,
     RightParen,
+    LeftBracket,  // [
+    RightBracket, // ]
     Comma,
     Dot,
+    DotDot, // ..
+    DotDotEqual, // ..=
     Minus,
     Semicolon,
     Slash,
+    SlashSlash, // Floor division
     Plus,
     Aster,
+    AsterAster, // Power
+    Percent,
+    PlusEqual,    // +=
+    MinusEqual,   // -=
+    AsterEqual,   // *=
+    SlashEqual,   // /=
+    PercentEqual, // %=
+    Pipe,         // | Around the parameters of a short lambda, between alternative patterns and bitwise or
+    Ampersand,    // &
+    Caret,        // ^ Bitwise xor
+    Tilde,        // ~ Bitwise not
+    LessLess,     // <<
+    GreaterGreater, // >>
+    FatArrow,     // =>
+    Arrow,        // -> Before the return type of a function
 
     //
     Not,
@@ -26,6 +48,7 @@ pub enum TokenType {
     //LITERALS
     Identifier,
     String,
+    Interpolation, // Text of a string up to a {
     Number,
 
     //KEYWORDS
@@ -36,6 +59,7 @@ pub enum TokenType {
     True,
     Fun,
     For,
+    In,
     If,
     Nil,
     Or,
@@ -47,6 +71,15 @@ pub enum TokenType {
     LeftSquigly,  // {}
     RightSquigly, // }
     Struct,
+    Enum,
+    Impl,
+    Match,
+    Try,
+    Catch,
+    Finally,
+    Throw,
+    Import,
+    Const,
     //
     TERMINATE,
     Break,
@@ -58,7 +91,9 @@ pub enum TokenType {
 pub enum Literal {
     // Identifier(String),
     Str(String),
-    Number(f64),
+    Int(i64),
+    BigInt(BigInt), // Whole numbers too big for an i64
+    Number(f64), // Any number with a . or an exponent in it
     True,
     False,
     Nil,
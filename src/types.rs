@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+use crate::token::{Token, TokenType};
+
+/*What the checker knows about a value. Any is anything at all, it is what unannotated code gets
+so it is never reported. Number is an Int or a Float. Structs, classes and enums are Named by their declaration */
+#[derive(Clone, PartialEq, Debug)]
+pub enum Type {
+    Any,
+    Number,
+    Int,
+    Float,
+    String,
+    Bool,
+    Nil,
+    List,
+    Dict,
+    Tuple,
+    Range,
+    Function,
+    Error,
+    Named(String),
+}
+
+impl Type {
+    // The type a name in an annotation stands for. Names that aren't built in are checked by the checker
+    pub fn from_annotation(name: &Token) -> Type {
+        if name.token_type == TokenType::Nil {
+            return Type::Nil;
+        }
+        match name.lex.as_str() {
+            "Any" => Type::Any,
+            "Number" => Type::Number,
+            "Int" => Type::Int,
+            "Float" => Type::Float,
+            "String" => Type::String,
+            "Bool" => Type::Bool,
+            "Nil" => Type::Nil,
+            "List" => Type::List,
+            "Dict" => Type::Dict,
+            "Tuple" => Type::Tuple,
+            "Range" => Type::Range,
+            "Function" => Type::Function,
+            "Error" => Type::Error,
+            n => Type::Named(n.to_string()),
+        }
+    }
+
+    pub fn is_any(&self) -> bool {
+        *self == Type::Any
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Type::Number | Type::Int | Type::Float)
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Named(n) => write!(f, "{}", n),
+            t => write!(f, "{:?}", t),
+        }
+    }
+}